@@ -0,0 +1,229 @@
+// Reusable converter and serializer carrying options plus custom handler
+// registries.
+//
+// `convert_with` and `mdast_to_string` cover the common case. `Converter` and
+// `Serializer` exist for callers who need to override how particular elements
+// are transformed or particular nodes are written, and want to build that
+// configuration once and reuse it across many documents.
+
+use std::fmt;
+
+use crate::hast_to_mdast::custom::{Element, ElementHandler, ElementHandlers};
+use crate::stringify::custom::{NodeHandler, NodeHandlers};
+use crate::{hast_to_mdast, mdast, stringify, Document, Options, StringifyOptions, TableData};
+
+/// A reusable HTML → Markdown converter.
+///
+/// Holds [`Options`] and a registry of custom [`ElementHandler`]s. Built-in
+/// handlers stay in effect for every element without a custom handler, and
+/// handlers registered later take precedence over earlier ones.
+///
+/// # Examples
+///
+/// ```
+/// use html2markdown::{mdast, Converter, Element, Options, TransformContext};
+///
+/// // Drop share widgets, keep everything else as usual.
+/// let converter = Converter::new(Options::new()).with_handler_when(
+///     |el: &Element| el.has_class("share"),
+///     |_: &mut TransformContext, _: &Element| Vec::<mdast::Node>::new(),
+/// );
+/// let md = converter.convert(r#"<p>Body</p><div class="share">Tweet</div>"#);
+/// assert_eq!(md, "Body\n");
+/// ```
+#[derive(Clone, Default)]
+pub struct Converter {
+    options: Options,
+    handlers: ElementHandlers,
+    node_handlers: NodeHandlers,
+}
+
+impl Converter {
+    /// Create a converter with the given options and no custom handlers.
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            handlers: ElementHandlers::default(),
+            node_handlers: NodeHandlers::default(),
+        }
+    }
+
+    /// The options this converter was built with.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Register a handler for every element with the given tag name.
+    ///
+    /// Tag names are matched case-insensitively.
+    pub fn with_handler<H>(mut self, tag: &str, handler: H) -> Self
+    where
+        H: ElementHandler + 'static,
+    {
+        self.handlers.insert_tag(tag, Box::new(handler));
+        self
+    }
+
+    /// Register a handler for every element for which `predicate` returns true.
+    pub fn with_handler_when<P, H>(mut self, predicate: P, handler: H) -> Self
+    where
+        P: Fn(&Element) -> bool + Send + Sync + 'static,
+        H: ElementHandler + 'static,
+    {
+        self.handlers
+            .insert_predicate(Box::new(predicate), Box::new(handler));
+        self
+    }
+
+    /// Register a serializer handler for every node of the given mdast type
+    /// (e.g. `"image"`). See [`Serializer::with_handler`].
+    pub fn with_node_handler<H>(mut self, type_name: &str, handler: H) -> Self
+    where
+        H: NodeHandler + 'static,
+    {
+        self.node_handlers.insert_type(type_name, Box::new(handler));
+        self
+    }
+
+    /// Register a serializer handler for every node for which `predicate`
+    /// returns true. See [`Serializer::with_handler_when`].
+    pub fn with_node_handler_when<P, H>(mut self, predicate: P, handler: H) -> Self
+    where
+        P: Fn(&mdast::Node) -> bool + Send + Sync + 'static,
+        H: NodeHandler + 'static,
+    {
+        self.node_handlers
+            .insert_predicate(Box::new(predicate), Box::new(handler));
+        self
+    }
+
+    /// Convert an HTML string to Markdown.
+    pub fn convert(&self, html: &str) -> String {
+        let tree = self.html_to_mdast(html);
+        stringify::stringify(&tree, &self.options.stringify, &self.node_handlers)
+    }
+
+    /// Parse HTML and transform it into an MDAST tree.
+    pub fn html_to_mdast(&self, html: &str) -> mdast::Node {
+        hast_to_mdast::transform(
+            html,
+            crate::transform_options(&self.options),
+            &self.handlers,
+            false,
+        )
+        .tree
+    }
+
+    /// Parse HTML and transform it into an MDAST tree, returning the page
+    /// metadata and structured data alongside it.
+    pub fn html_to_document(&self, html: &str) -> Document {
+        hast_to_mdast::transform(
+            html,
+            crate::transform_options(&self.options),
+            &self.handlers,
+            true,
+        )
+    }
+
+    /// Every outermost table in `html` as data. See [`crate::extract_tables`].
+    pub fn extract_tables(&self, html: &str) -> Vec<TableData> {
+        hast_to_mdast::extract_tables(
+            html,
+            crate::transform_options(&self.options),
+            &self.handlers,
+        )
+    }
+}
+
+impl fmt::Debug for Converter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Converter")
+            .field("options", &self.options)
+            .field("handlers", &self.handlers.len())
+            .field("node_handlers", &self.node_handlers.len())
+            .finish()
+    }
+}
+
+/// A reusable MDAST → Markdown serializer.
+///
+/// Holds [`StringifyOptions`] and a registry of custom [`NodeHandler`]s.
+/// Nodes without a custom handler are written by the built-in serializer, and
+/// handlers registered later take precedence over earlier ones.
+///
+/// # Examples
+///
+/// ```
+/// use html2markdown::mdast::Node;
+/// use html2markdown::{Options, Serializer, StringifyContext, StringifyOptions};
+///
+/// // Rewrite link targets on the way out.
+/// let serializer = Serializer::new(StringifyOptions::default()).with_handler(
+///     "link",
+///     |cx: &mut StringifyContext, node: &Node| match node {
+///         Node::Link(link) => {
+///             let mut link = link.clone();
+///             link.url = link.url.replace(".html", "/");
+///             cx.default(&Node::Link(link))
+///         }
+///         _ => unreachable!(),
+///     },
+/// );
+/// let tree = html2markdown::html_to_mdast(r#"<a href="guide.html">Guide</a>"#, &Options::new());
+/// assert_eq!(serializer.stringify(&tree), "[Guide](guide/)\n");
+/// ```
+#[derive(Clone, Default)]
+pub struct Serializer {
+    options: StringifyOptions,
+    handlers: NodeHandlers,
+}
+
+impl Serializer {
+    /// Create a serializer with the given options and no custom handlers.
+    pub fn new(options: StringifyOptions) -> Self {
+        Self {
+            options,
+            handlers: NodeHandlers::default(),
+        }
+    }
+
+    /// The options this serializer was built with.
+    pub fn options(&self) -> &StringifyOptions {
+        &self.options
+    }
+
+    /// Register a handler for every node of the given mdast type (the value
+    /// returned by [`mdast::Node::type_name`], e.g. `"image"` or `"inlineCode"`).
+    pub fn with_handler<H>(mut self, type_name: &str, handler: H) -> Self
+    where
+        H: NodeHandler + 'static,
+    {
+        self.handlers.insert_type(type_name, Box::new(handler));
+        self
+    }
+
+    /// Register a handler for every node for which `predicate` returns true.
+    pub fn with_handler_when<P, H>(mut self, predicate: P, handler: H) -> Self
+    where
+        P: Fn(&mdast::Node) -> bool + Send + Sync + 'static,
+        H: NodeHandler + 'static,
+    {
+        self.handlers
+            .insert_predicate(Box::new(predicate), Box::new(handler));
+        self
+    }
+
+    /// Serialize an MDAST tree to a Markdown string.
+    pub fn stringify(&self, node: &mdast::Node) -> String {
+        stringify::stringify(node, &self.options, &self.handlers)
+    }
+}
+
+impl fmt::Debug for Serializer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Serializer")
+            .field("options", &self.options)
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
@@ -0,0 +1,166 @@
+// Admonitions: notes, tips and warnings in documentation pages.
+//
+// Sphinx and MkDocs write `<div class="admonition warning">` with a
+// `<p class="admonition-title">`, Docusaurus `alert--warning` with an
+// `admonitionHeading_*` header, Bootstrap `alert-warning`, and Confluence
+// `confluence-information-macro-*` panels with a `<p class="title">`; other
+// sites use `<aside class="note">` or `role="note"`. Classes map to a kind
+// through `TransformOptions::admonition_kinds`.
+
+use std::rc::Rc;
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::handlers::{get_attr, has_class, one, to_text};
+use super::util::normalize_whitespace;
+use super::State;
+use crate::mdast::{self, Node};
+
+/// Built-in class → kind mapping, checked in order. Kinds are GitHub's alert
+/// types; the generic markers at the end default to `note`.
+pub(crate) const DEFAULT_KINDS: &[(&str, &str)] = &[
+    ("note", "note"),
+    ("info", "note"),
+    ("seealso", "note"),
+    ("alert-info", "note"),
+    ("alert--info", "note"),
+    ("alert--secondary", "note"),
+    ("confluence-information-macro-information", "note"),
+    ("tip", "tip"),
+    ("hint", "tip"),
+    ("success", "tip"),
+    ("alert-success", "tip"),
+    ("alert--success", "tip"),
+    ("confluence-information-macro-tip", "tip"),
+    ("important", "important"),
+    ("attention", "important"),
+    ("alert-primary", "important"),
+    ("alert--primary", "important"),
+    ("warning", "warning"),
+    ("warn", "warning"),
+    ("alert-warning", "warning"),
+    ("alert--warning", "warning"),
+    ("confluence-information-macro-note", "warning"),
+    ("caution", "caution"),
+    ("danger", "caution"),
+    ("error", "caution"),
+    ("alert-danger", "caution"),
+    ("alert--danger", "caution"),
+    ("confluence-information-macro-warning", "caution"),
+    ("admonition", "note"),
+    ("callout", "note"),
+    ("alert", "note"),
+    ("confluence-information-macro", "note"),
+];
+
+/// Classes of an admonition's title element.
+const TITLE_CLASSES: &[&str] = &["admonition-title", "title", "alert-heading"];
+
+/// Class prefixes marking an element as an admonition, so that its
+/// single-word classes (`note`, `info`, `error`, …) can be trusted.
+const MARKER_PREFIXES: &[&str] = &["admonition", "alert-", "callout-"];
+
+/// The kind of admonition `handle` is, if any: the first mapped class it
+/// has, or `note` for `role="note"`. Single-word classes are common on
+/// ordinary layout containers, so they only count on marked elements.
+pub(crate) fn kind(handle: &Handle, tag: &str, kinds: &[(String, String)]) -> Option<String> {
+    let marked = is_marked(handle, tag);
+    kinds
+        .iter()
+        .filter(|(class, _)| marked || class.contains(['-', '_']))
+        .find(|(class, _)| has_class(handle, class))
+        .map(|(_, kind)| kind.to_ascii_lowercase())
+        .or_else(|| (get_attr(handle, "role").as_deref() == Some("note")).then(|| "note".into()))
+}
+
+/// Whether `handle` is marked as an admonition: an `<aside>`, `role="note"`
+/// or `role="alert"`, or a class starting with one of [`MARKER_PREFIXES`].
+fn is_marked(handle: &Handle, tag: &str) -> bool {
+    tag == "aside"
+        || get_attr(handle, "role").is_some_and(|role| role == "note" || role == "alert")
+        || get_attr(handle, "class").is_some_and(|classes| {
+            classes.split_whitespace().any(|class| {
+                MARKER_PREFIXES
+                    .iter()
+                    .any(|prefix| class.starts_with(prefix))
+            })
+        })
+}
+
+/// Admonition element → `Admonition`. A leading title element becomes the
+/// title, unless it only names the kind (`Warning`, or `Danger` for a
+/// `danger` → `caution` mapping).
+pub(crate) fn handle(state: &mut State, handle: &Handle, kind: String) -> Vec<Node> {
+    let title_handle = handle
+        .children
+        .borrow()
+        .iter()
+        .find(|child| matches!(child.data, NodeData::Element { .. }))
+        .filter(|child| is_title(child))
+        .cloned();
+    let title = title_handle
+        .as_ref()
+        .map(|title| normalize_whitespace(&to_text(title)))
+        .filter(|title| !title.is_empty() && !names_kind(state, title, &kind));
+
+    let mut nodes = Vec::new();
+    for child in handle.children.borrow().iter() {
+        if title_handle
+            .as_ref()
+            .is_some_and(|title| Rc::ptr_eq(title, child))
+        {
+            continue;
+        }
+        nodes.extend(one(state, child));
+    }
+    let children = super::wrap::wrap(nodes);
+    if children.is_empty() && title.is_none() {
+        return vec![];
+    }
+    vec![Node::Admonition(mdast::Admonition {
+        kind,
+        title,
+        children,
+    })]
+}
+
+fn names_kind(state: &State, title: &str, kind: &str) -> bool {
+    title.eq_ignore_ascii_case(kind)
+        || state
+            .options
+            .admonition_kinds
+            .iter()
+            .any(|(class, mapped)| {
+                mapped.eq_ignore_ascii_case(kind) && title.eq_ignore_ascii_case(class)
+            })
+}
+
+fn is_title(handle: &Handle) -> bool {
+    TITLE_CLASSES.iter().any(|class| has_class(handle, class))
+        || get_attr(handle, "class").is_some_and(|classes| {
+            classes
+                .split_whitespace()
+                .any(|class| class.starts_with("admonitionHeading"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_kinds_prefer_specific_classes() {
+        let kinds: Vec<(String, String)> = DEFAULT_KINDS
+            .iter()
+            .map(|&(class, kind)| (class.to_string(), kind.to_string()))
+            .collect();
+        let position = |class: &str| kinds.iter().position(|(c, _)| c == class);
+        assert!(position("alert-warning") < position("alert"));
+        assert!(
+            position("confluence-information-macro-tip") < position("confluence-information-macro")
+        );
+        assert!(DEFAULT_KINDS.iter().all(|(_, kind)| {
+            ["note", "tip", "important", "warning", "caution"].contains(kind)
+        }));
+    }
+}
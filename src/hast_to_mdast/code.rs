@@ -0,0 +1,578 @@
+// Code block helpers: language detection and text cleanup.
+//
+// Syntax highlighters mark the language in different places: `language-*`
+// (Prism, markdown renderers) or `lang-*` (highlight.js) classes on `<code>`
+// or `<pre>`, `data-lang`/`data-language` attributes, and classes on wrapper
+// elements such as GitHub's `highlight-source-rust`, Sphinx/Pygments'
+// `highlight-python`, and Docusaurus/MkDocs' `language-*` containers.
+//
+// Documentation generators also decorate the code itself: line-number
+// gutters (Pygments/Rouge/Chroma tables, `span.linenos`, Prism's
+// `line-numbers-rows`, highlightjs-line-numbers), "Copy" buttons, and shell
+// or REPL prompts. Those are removed so the fenced block holds only code.
+//
+// Titles and highlighted line ranges become the fence's meta string
+// (`title="main.rs" {3-5}`), from attributes or from a caption next to the
+// code (`<figcaption>`, MkDocs' `span.filename`, Sphinx's
+// `code-block-caption`, Docusaurus' `codeBlockTitle`).
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::handlers::{get_attr, has_class, is_tag, to_text};
+use super::select::parent_element;
+use super::util::normalize_whitespace;
+use super::{State, MAX_DEPTH};
+
+/// How many wrapper elements above `<pre>` are searched.
+const MAX_ANCESTORS: usize = 3;
+
+/// Built-in alias → language mapping, added by
+/// `Options::with_default_language_aliases`: common short names to the names
+/// [`guess_language`](super::guess::guess_language) uses, and highlight.js'
+/// `nohighlight` to no language.
+pub(crate) const DEFAULT_LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("ts", "typescript"),
+    ("py", "python"),
+    ("py3", "python"),
+    ("python3", "python"),
+    ("rb", "ruby"),
+    ("rs", "rust"),
+    ("golang", "go"),
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("zsh", "bash"),
+    ("ps1", "powershell"),
+    ("pwsh", "powershell"),
+    ("yml", "yaml"),
+    ("md", "markdown"),
+    ("kt", "kotlin"),
+    ("cs", "csharp"),
+    ("c#", "csharp"),
+    ("c++", "cpp"),
+    ("cxx", "cpp"),
+    ("objc", "objectivec"),
+    ("nohighlight", ""),
+];
+
+/// Find the language of a `<pre>` block: its `<code>` child first, then the
+/// `<pre>` itself, then up to [`MAX_ANCESTORS`] wrappers of `outer` (the
+/// `<pre>`, or the gutter table around it). The name goes through
+/// [`alias_language`], so an empty result means the block is explicitly
+/// labelled as having no language.
+pub(crate) fn find_code_language(state: &State, pre: &Handle, outer: &Handle) -> Option<String> {
+    let code = pre
+        .children
+        .borrow()
+        .iter()
+        .find(|child| is_tag(child, "code"))
+        .cloned();
+    let ancestors =
+        std::iter::successors(parent_element(outer), parent_element).take(MAX_ANCESTORS);
+
+    let found = code
+        .iter()
+        .find_map(|code| language_of(code, false))
+        .or_else(|| language_of(pre, false))
+        .or_else(|| {
+            ancestors
+                .map(|ancestor| language_of(&ancestor, true))
+                .find(Option::is_some)
+                .flatten()
+        })?;
+    Some(alias_language(state, found))
+}
+
+/// Look `language` up case-insensitively in the alias map; an alias to `""`
+/// means "no language".
+pub(crate) fn alias_language(state: &State, language: String) -> String {
+    match state.options.language_aliases.get(&language.to_lowercase()) {
+        Some(alias) => alias.clone(),
+        None => language,
+    }
+}
+
+/// The language named on `handle`. Wrapper classes (`highlight-*`) are only
+/// trusted on ancestors, where they cannot be confused with token classes.
+fn language_of(handle: &Handle, wrapper: bool) -> Option<String> {
+    if !matches!(handle.data, NodeData::Element { .. }) {
+        return None;
+    }
+    for attr in ["data-lang", "data-language"] {
+        if let Some(value) = get_attr(handle, attr).filter(|v| is_language(v.trim())) {
+            return Some(value.trim().to_string());
+        }
+    }
+    let class = get_attr(handle, "class").unwrap_or_default();
+    class.split_ascii_whitespace().find_map(|class| {
+        let language = class
+            .strip_prefix("language-")
+            .or_else(|| class.strip_prefix("lang-"))
+            .or_else(|| wrapper.then(|| wrapper_language(class)).flatten())?;
+        is_language(language).then(|| language.to_string())
+    })
+}
+
+/// GitHub's `highlight-source-rust` / `highlight-text-html-basic` and
+/// Pygments' `highlight-python` (Sphinx's `highlight-default` names no
+/// language).
+fn wrapper_language(class: &str) -> Option<&str> {
+    if let Some(scope) = class.strip_prefix("highlight-source-") {
+        return Some(scope);
+    }
+    if let Some(scope) = class.strip_prefix("highlight-text-") {
+        return scope.split('-').next();
+    }
+    class
+        .strip_prefix("highlight-")
+        .filter(|language| *language != "default")
+}
+
+/// Whether `value` can be written as a fence info string language.
+fn is_language(value: &str) -> bool {
+    !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || c == '`' || c == '~')
+}
+
+// ---------------------------------------------------------------------------
+// Meta
+// ---------------------------------------------------------------------------
+
+/// Built-in attribute → meta mapping; `{}` is replaced by the value.
+pub(crate) const DEFAULT_META_ATTRIBUTES: &[(&str, &str)] = &[
+    ("data-title", "title=\"{}\""),
+    ("data-filename", "title=\"{}\""),
+    ("data-file", "title=\"{}\""),
+    ("data-line", "{{}}"),
+];
+
+/// Template used for caption text.
+const CAPTION_TEMPLATE: &str = "title=\"{}\"";
+
+/// Classes of wrapper headers that name a code block.
+const CAPTION_CLASSES: &[&str] = &["code-block-caption", "filename", "file-name", "code-title"];
+
+/// Longest caption taken as a title.
+const MAX_CAPTION_LEN: usize = 100;
+
+/// Collect the meta parts for a code block from attributes on the `<pre>`,
+/// its `<code>` child and the wrappers of `outer`, then from a caption.
+/// Each template is used once, by the first element that provides it.
+pub(crate) fn code_meta(state: &State, pre: &Handle, outer: &Handle) -> Vec<String> {
+    let code = pre
+        .children
+        .borrow()
+        .iter()
+        .find(|child| is_tag(child, "code"))
+        .cloned();
+    let wrappers: Vec<Handle> = std::iter::successors(parent_element(outer), parent_element)
+        .take(MAX_ANCESTORS)
+        .collect();
+    let elements: Vec<&Handle> = code.iter().chain([pre, outer]).chain(&wrappers).collect();
+
+    let mut templates: Vec<&str> = Vec::new();
+    let mut parts = Vec::new();
+    for (attr, template) in &state.options.code_meta_attributes {
+        if templates.contains(&template.as_str()) {
+            continue;
+        }
+        let value = elements
+            .iter()
+            .find_map(|element| get_attr(element, attr))
+            .map(|value| normalize_whitespace(&value))
+            .filter(|value| !value.is_empty());
+        if let Some(value) = value {
+            templates.push(template);
+            parts.push(apply_template(template, &value));
+        }
+    }
+    if !templates.contains(&CAPTION_TEMPLATE) {
+        let caption = wrappers.iter().find_map(|wrapper| {
+            wrapper
+                .children
+                .borrow()
+                .iter()
+                .find(|child| is_code_caption(state, child))
+                .map(caption_text)
+        });
+        if let Some(caption) = caption {
+            parts.insert(0, apply_template(CAPTION_TEMPLATE, &caption));
+        }
+    }
+    parts
+}
+
+fn apply_template(template: &str, value: &str) -> String {
+    let value = if template.contains("\"{}\"") {
+        value.replace('"', "\\\"")
+    } else {
+        value.to_string()
+    };
+    template.replacen("{}", &value, 1)
+}
+
+/// Whether `handle` is a short caption naming the single code block in its
+/// parent. Such captions become meta and are not converted where they stand.
+pub(crate) fn is_code_caption(state: &State, handle: &Handle) -> bool {
+    let captionish = is_tag(handle, "figcaption")
+        || CAPTION_CLASSES.iter().any(|class| has_class(handle, class))
+        || get_attr(handle, "class").is_some_and(|class| {
+            class
+                .split_ascii_whitespace()
+                .any(|c| c.starts_with("codeBlockTitle"))
+        });
+    if !captionish {
+        return false;
+    }
+    let text = caption_text(handle);
+    if text.is_empty() || text.chars().count() > MAX_CAPTION_LEN {
+        return false;
+    }
+    // Meta needs a language to follow, so without one the caption stays.
+    let Some(parent) = parent_element(handle) else {
+        return false;
+    };
+    match descendants(&parent, "pre").as_slice() {
+        [pre] => find_code_language(state, pre, pre).is_some_and(|lang| !lang.is_empty()),
+        _ => false,
+    }
+}
+
+/// Caption text without Sphinx's `¶` permalink.
+fn caption_text(handle: &Handle) -> String {
+    normalize_whitespace(&super::handlers::to_text_without(handle, |h| {
+        has_class(h, "headerlink")
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Cleanup
+// ---------------------------------------------------------------------------
+
+/// Classes of line-number gutters inside code.
+const GUTTER_CLASSES: &[&str] = &[
+    "linenos",
+    "lineno",
+    "lnt",
+    "ln",
+    "line-numbers-rows",
+    "hljs-ln-numbers",
+    "rouge-gutter",
+];
+
+/// Classes of copy-to-clipboard buttons.
+const COPY_CLASSES: &[&str] = &[
+    "copy",
+    "copybtn",
+    "copy-button",
+    "btn-copy",
+    "code-copy",
+    "copy-code-button",
+    "clipboard",
+    "clipboard-button",
+    "js-clipboard-copy",
+];
+
+/// Whether `handle` is a copy-to-clipboard button: a `<button>` (or
+/// `<clipboard-copy>`, or an element with a copy class) whose label starts
+/// with the word "Copy"/"Copied". Unlabelled ones count only with a copy
+/// class.
+fn is_copy_button(handle: &Handle) -> bool {
+    let copy_class = COPY_CLASSES.iter().any(|class| has_class(handle, class));
+    if !(copy_class || is_tag(handle, "button") || is_tag(handle, "clipboard-copy")) {
+        return false;
+    }
+    let text = to_text(handle);
+    let label = match text.trim() {
+        "" => get_attr(handle, "aria-label")
+            .or_else(|| get_attr(handle, "title"))
+            .unwrap_or_default(),
+        text => text.to_string(),
+    };
+    let label = label.trim().to_lowercase();
+    let first_word = label
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(|c: char| c.is_ascii_punctuation());
+    (label.is_empty() && copy_class) || matches!(first_word, "copy" | "copied")
+}
+
+/// Whether `handle` is a copy button next to a code block, with
+/// `code_strip_chrome`: a sibling of a `<pre>` or of a wrapper holding one.
+/// Copy buttons inside the `<pre>` are left out by [`code_text`].
+pub(crate) fn is_code_copy_button(state: &State, handle: &Handle) -> bool {
+    if !state.options.code_strip_chrome
+        || !is_copy_button(handle)
+        || !descendants(handle, "pre").is_empty()
+    {
+        return false;
+    }
+    let Some(parent) = super::select::parent_element(handle) else {
+        return false;
+    };
+    let siblings = parent.children.borrow();
+    siblings.iter().any(|sibling| {
+        !std::rc::Rc::ptr_eq(sibling, handle)
+            && (is_tag(sibling, "pre") || !descendants(sibling, "pre").is_empty())
+    })
+}
+
+/// Whether `handle` is a line-number gutter or a button inside code.
+fn is_chrome(handle: &Handle) -> bool {
+    is_tag(handle, "button")
+        || GUTTER_CLASSES.iter().any(|class| has_class(handle, class))
+        || is_copy_button(handle)
+}
+
+/// Text of a code block, with gutters and buttons left out if
+/// `code_strip_chrome` is set.
+pub(crate) fn code_text(state: &State, handle: &Handle) -> String {
+    if !state.options.code_strip_chrome {
+        return to_text(handle);
+    }
+    super::handlers::to_text_without(handle, is_chrome)
+}
+
+/// The `<pre>` holding the code of a line-number table, with
+/// `code_strip_chrome`: one row with a gutter cell (by class, or only
+/// digits) followed by a cell with a `<pre>`.
+pub(crate) fn gutter_table_code(state: &State, table: &Handle) -> Option<Handle> {
+    if !state.options.code_strip_chrome {
+        return None;
+    }
+    let rows = descendants(table, "tr");
+    let [row] = rows.as_slice() else {
+        return None;
+    };
+    let cells: Vec<Handle> = row
+        .children
+        .borrow()
+        .iter()
+        .filter(|c| is_tag(c, "td") || is_tag(c, "th"))
+        .cloned()
+        .collect();
+    let [gutter, code] = cells.as_slice() else {
+        return None;
+    };
+    let gutter_text = to_text(gutter);
+    let is_gutter = GUTTER_CLASSES.iter().any(|class| has_class(gutter, class))
+        || has_class(gutter, "lntd") && !descendants(gutter, "pre").is_empty()
+        || (!gutter_text.trim().is_empty()
+            && gutter_text
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_whitespace()));
+    if !is_gutter {
+        return None;
+    }
+    descendants(code, "pre").into_iter().next()
+}
+
+/// Elements named `tag` below `handle`, not descending into matches.
+fn descendants(handle: &Handle, tag: &str) -> Vec<Handle> {
+    fn walk(handle: &Handle, tag: &str, found: &mut Vec<Handle>, depth: usize) {
+        if depth >= MAX_DEPTH {
+            return;
+        }
+        for child in handle.children.borrow().iter() {
+            if is_tag(child, tag) {
+                found.push(child.clone());
+            } else {
+                walk(child, tag, found, depth + 1);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    walk(handle, tag, &mut found, 0);
+    found
+}
+
+/// Remove the longest run of leading whitespace shared by all non-blank
+/// lines.
+pub(crate) fn dedent(code: &str) -> String {
+    let indent = code
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|common, indent| {
+            let shared = common
+                .char_indices()
+                .zip(indent.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(indent.len()), |((i, _), _)| i);
+            &common[..shared]
+        })
+        .unwrap_or_default();
+    if indent.is_empty() {
+        return code.to_string();
+    }
+    code.split('\n')
+        .map(|line| line.strip_prefix(indent).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A prompt recognized at the start of a session.
+struct Prompt {
+    prompt: &'static str,
+    /// Prompt of continuation lines, if any.
+    continuation: Option<&'static str>,
+    /// Language recorded for blocks that have none.
+    language: &'static str,
+}
+
+const PROMPTS: &[Prompt] = &[
+    Prompt {
+        prompt: "$ ",
+        continuation: Some("> "),
+        language: "console",
+    },
+    Prompt {
+        prompt: "% ",
+        continuation: None,
+        language: "console",
+    },
+    Prompt {
+        prompt: ">>> ",
+        continuation: Some("... "),
+        language: "pycon",
+    },
+];
+
+/// Code with its prompts stripped.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Stripped {
+    pub code: String,
+    /// The prompt that was removed, e.g. `$`.
+    pub prompt: &'static str,
+    /// Language for the block if it has none (`console`, `pycon`).
+    pub language: &'static str,
+}
+
+/// Strip shell (`$ `, `% `) or Python REPL (`>>> `) prompts when the block
+/// starts with one. Lines without a prompt (output) are kept as they are.
+/// A shell `> ` line only continues a command left unfinished (trailing
+/// `\`, `|`, `&&` or `||`, or an open quote); otherwise it is output.
+pub(crate) fn strip_prompts(code: &str) -> Option<Stripped> {
+    let first = code.lines().find(|line| !line.trim().is_empty())?;
+    let prompt = PROMPTS
+        .iter()
+        .find(|prompt| first.starts_with(prompt.prompt))?;
+    let shell = prompt.language == "console";
+    let mut continues = false;
+    let mut quote = None;
+    let stripped: Vec<&str> = code
+        .split('\n')
+        .map(|line| {
+            let rest = if let Some(rest) = line.strip_prefix(prompt.prompt) {
+                quote = None;
+                rest
+            } else if let Some(rest) = prompt
+                .continuation
+                .filter(|_| continues)
+                .and_then(|continuation| line.strip_prefix(continuation))
+            {
+                rest
+            } else {
+                continues = false;
+                return line;
+            };
+            continues = !shell || is_unfinished(rest, &mut quote);
+            rest
+        })
+        .collect();
+    Some(Stripped {
+        code: stripped.join("\n"),
+        prompt: prompt.prompt.trim_end(),
+        language: prompt.language,
+    })
+}
+
+/// Whether a shell command line continues on the next line. `quote` carries
+/// an open quote across lines.
+fn is_unfinished(line: &str, quote: &mut Option<char>) -> bool {
+    let mut escaped = false;
+    for c in line.chars() {
+        match (*quote, c) {
+            (_, _) if escaped => escaped = false,
+            (Some('\''), '\'') => *quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => escaped = true,
+            (Some('"'), '"') => *quote = None,
+            (None, '\'' | '"') => *quote = Some(c),
+            _ => {}
+        }
+    }
+    let line = line.trim_end();
+    quote.is_some() || line.ends_with('\\') || line.ends_with('|') || line.ends_with("&&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapper_classes() {
+        assert_eq!(wrapper_language("highlight-source-rust"), Some("rust"));
+        assert_eq!(wrapper_language("highlight-text-html-basic"), Some("html"));
+        assert_eq!(wrapper_language("highlight-python"), Some("python"));
+        assert_eq!(wrapper_language("highlight-default"), None);
+        assert_eq!(wrapper_language("highlight"), None);
+        assert_eq!(wrapper_language("notranslate"), None);
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(apply_template("{{}}", "3-5"), "{3-5}");
+        assert_eq!(
+            apply_template("title=\"{}\"", "a \"b\""),
+            "title=\"a \\\"b\\\"\""
+        );
+        assert_eq!(apply_template("hl_lines={}", "2"), "hl_lines=2");
+    }
+
+    #[test]
+    fn dedent_common_indent() {
+        assert_eq!(dedent("    a\n      b\n\n    c"), "a\n  b\n\nc");
+        assert_eq!(dedent("\tx\n\ty"), "x\ny");
+        assert_eq!(dedent("a\n  b"), "a\n  b");
+    }
+
+    #[test]
+    fn prompts() {
+        assert_eq!(
+            strip_prompts("$ ls\nfile\n$ echo \\\n> x"),
+            Some(Stripped {
+                code: "ls\nfile\necho \\\nx".to_string(),
+                prompt: "$",
+                language: "console",
+            })
+        );
+        assert_eq!(
+            strip_prompts(">>> for i in x:\n...     i\n1"),
+            Some(Stripped {
+                code: "for i in x:\n    i\n1".to_string(),
+                prompt: ">>>",
+                language: "pycon",
+            })
+        );
+        assert_eq!(strip_prompts("echo $ x"), None);
+    }
+
+    #[test]
+    fn shell_output_quoted_with_gt_is_kept() {
+        let stripped = |code| strip_prompts(code).unwrap().code;
+        assert_eq!(stripped("$ cat notes\n> quoted"), "cat notes\n> quoted");
+        assert_eq!(stripped("$ echo 'a\n> b'\n> c"), "echo 'a\nb'\n> c");
+        assert_eq!(stripped("$ a |\n> b\n> c"), "a |\nb\n> c");
+    }
+
+    #[test]
+    fn fence_safe_languages() {
+        assert!(is_language("c++"));
+        assert!(!is_language(""));
+        assert!(!is_language("a`b"));
+        assert!(!is_language("two words"));
+    }
+}
@@ -0,0 +1,234 @@
+// Custom element handlers.
+//
+// Lets callers replace the built-in conversion of specific elements without
+// forking `dispatch_element`. Handlers are registered on a `Converter` and
+// looked up before the built-in match, keyed by tag name or by a predicate.
+
+use std::fmt;
+use std::sync::Arc;
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::State;
+use crate::mdast;
+
+/// A user-supplied conversion for HTML elements.
+///
+/// Implemented for any `Fn(&mut TransformContext, &Element) -> Vec<Node>`
+/// closure, so most handlers can be written inline:
+///
+/// ```rust
+/// use html2markdown::{mdast, Converter, Element, Options, TransformContext};
+///
+/// let converter = Converter::new(Options::new()).with_handler(
+///     "aside",
+///     |cx: &mut TransformContext, el: &Element| {
+///         let children = cx.all(el);
+///         vec![mdast::Node::Blockquote(mdast::Blockquote {
+///             children: cx.wrap(children),
+///         })]
+///     },
+/// );
+/// assert_eq!(converter.convert("<aside>Note</aside>"), "> Note\n");
+/// ```
+pub trait ElementHandler: Send + Sync {
+    /// Convert `element` into zero or more MDAST nodes.
+    fn handle(&self, cx: &mut TransformContext, element: &Element) -> Vec<mdast::Node>;
+}
+
+impl<F> ElementHandler for F
+where
+    F: Fn(&mut TransformContext, &Element) -> Vec<mdast::Node> + Send + Sync,
+{
+    fn handle(&self, cx: &mut TransformContext, element: &Element) -> Vec<mdast::Node> {
+        self(cx, element)
+    }
+}
+
+/// Read-only view of an HTML element passed to an [`ElementHandler`].
+#[derive(Clone)]
+pub struct Element {
+    pub(crate) handle: Handle,
+}
+
+impl Element {
+    pub(crate) fn new(handle: &Handle) -> Self {
+        Self {
+            handle: handle.clone(),
+        }
+    }
+
+    /// Lowercase tag name (e.g. `"aside"`, `"my-widget"`).
+    pub fn tag(&self) -> &str {
+        match self.handle.data {
+            NodeData::Element { ref name, .. } => name.local.as_ref(),
+            _ => "",
+        }
+    }
+
+    /// Value of the attribute `name`, if present.
+    pub fn attr(&self, name: &str) -> Option<String> {
+        super::handlers::get_attr(&self.handle, name)
+    }
+
+    /// Whether the attribute `name` is present.
+    pub fn has_attr(&self, name: &str) -> bool {
+        super::handlers::has_attr(&self.handle, name)
+    }
+
+    /// All attributes as `(name, value)` pairs, in document order.
+    pub fn attrs(&self) -> Vec<(String, String)> {
+        match self.handle.data {
+            NodeData::Element { ref attrs, .. } => attrs
+                .borrow()
+                .iter()
+                .map(|a| (a.name.local.to_string(), a.value.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the `class` attribute contains `class` as a whole word.
+    pub fn has_class(&self, class: &str) -> bool {
+        super::handlers::has_class(&self.handle, class)
+    }
+
+    /// Whether the element matches `selector`.
+    pub fn matches(&self, selector: &super::select::Selector) -> bool {
+        selector.matches(&self.handle)
+    }
+
+    /// Why this table cannot be written as a GFM table without losing
+    /// structure; empty if it can, or if the element is not a `<table>`.
+    pub fn table_losses(&self) -> Vec<super::tables::TableLoss> {
+        super::tables::losses(&self.handle)
+    }
+
+    /// Child elements, in document order (text and comments are skipped).
+    pub fn children(&self) -> Vec<Element> {
+        self.handle
+            .children
+            .borrow()
+            .iter()
+            .filter(|child| matches!(child.data, NodeData::Element { .. }))
+            .map(Element::new)
+            .collect()
+    }
+
+    /// Text content of the element and its descendants.
+    pub fn text(&self) -> String {
+        super::handlers::to_text(&self.handle)
+    }
+}
+
+impl fmt::Debug for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Element").field("tag", &self.tag()).finish()
+    }
+}
+
+/// Access to the transformer from inside an [`ElementHandler`].
+///
+/// Recursing through the context keeps the usual machinery in play: base URL
+/// resolution, `<pre>` whitespace handling, the depth limit, and any other
+/// registered handlers for descendants.
+pub struct TransformContext<'s> {
+    pub(crate) state: &'s mut State,
+}
+
+impl TransformContext<'_> {
+    /// Convert all children of `element`.
+    pub fn all(&mut self, element: &Element) -> Vec<mdast::Node> {
+        super::handlers::all(self.state, &element.handle)
+    }
+
+    /// Convert `element` itself, consulting registered handlers first.
+    ///
+    /// Calling this on the element currently being handled re-enters the same
+    /// handler; use [`TransformContext::default`] for that instead.
+    pub fn one(&mut self, element: &Element) -> Vec<mdast::Node> {
+        super::handlers::one(self.state, &element.handle)
+    }
+
+    /// Convert `element` with the built-in handler, ignoring custom ones.
+    pub fn default(&mut self, element: &Element) -> Vec<mdast::Node> {
+        super::handlers::dispatch_element(self.state, &element.handle, element.tag())
+    }
+
+    /// Wrap mixed content: phrasing runs become paragraphs.
+    pub fn wrap(&self, nodes: Vec<mdast::Node>) -> Vec<mdast::Node> {
+        super::wrap::wrap(nodes)
+    }
+
+    /// Resolve a URL against the document's `<base>`, if any.
+    pub fn resolve(&self, url: &str) -> String {
+        self.state.resolve(url)
+    }
+}
+
+type Predicate = dyn Fn(&Element) -> bool + Send + Sync;
+
+/// Which elements a registered handler applies to.
+enum Matcher {
+    Tag(String),
+    Predicate(Box<Predicate>),
+}
+
+impl Matcher {
+    fn matches(&self, element: &Element) -> bool {
+        match self {
+            Matcher::Tag(tag) => element.tag() == tag,
+            Matcher::Predicate(predicate) => predicate(element),
+        }
+    }
+}
+
+/// A registered handler and the elements it applies to.
+pub(crate) struct Entry {
+    matcher: Matcher,
+    pub handler: Box<dyn ElementHandler>,
+}
+
+/// Registered custom handlers, consulted before the built-in dispatch.
+///
+/// Entries are reference-counted so cloning the registry into each
+/// transformation's `State` is cheap.
+#[derive(Clone, Default)]
+pub(crate) struct ElementHandlers {
+    entries: Vec<Arc<Entry>>,
+}
+
+impl ElementHandlers {
+    /// Register a handler for a tag name.
+    pub fn insert_tag(&mut self, tag: &str, handler: Box<dyn ElementHandler>) {
+        let matcher = Matcher::Tag(tag.to_ascii_lowercase());
+        self.entries.push(Arc::new(Entry { matcher, handler }));
+    }
+
+    /// Register a handler for elements matching `predicate`.
+    pub fn insert_predicate(
+        &mut self,
+        predicate: Box<Predicate>,
+        handler: Box<dyn ElementHandler>,
+    ) {
+        let matcher = Matcher::Predicate(predicate);
+        self.entries.push(Arc::new(Entry { matcher, handler }));
+    }
+
+    /// Find the handler for `handle`. Later registrations take precedence.
+    pub fn find(&self, handle: &Handle) -> Option<Arc<Entry>> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let element = Element::new(handle);
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.matcher.matches(&element))
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
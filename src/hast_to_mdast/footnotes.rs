@@ -0,0 +1,214 @@
+// Footnote detection.
+//
+// Recognizes the footnote markup produced by Pandoc (`role="doc-noteref"`,
+// `a.footnote-ref`, `section.footnotes`), Wikipedia (`sup.reference`,
+// `ol.references`), WordPress (`ol.wp-block-footnotes`), Sphinx
+// (`a.footnote-reference`, `aside.footnote`) and GitHub (`data-footnote-ref`,
+// `section[data-footnotes]`), and turns it into GFM footnote nodes.
+//
+// A pre-pass finds every in-page link that points at a footnote; those
+// targets are then dropped where they appear in the document and emitted as
+// `FootnoteDefinition`s at the end, numbered in order of first reference.
+
+use std::collections::{HashMap, HashSet};
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::handlers::{all, get_attr, has_attr, has_class, is_tag};
+use super::select::parent_element;
+use super::{State, MAX_DEPTH};
+use crate::mdast;
+
+/// Footnote bookkeeping threaded through `State`.
+#[derive(Default)]
+pub(crate) struct Footnotes {
+    /// Ids of elements that are the target of at least one footnote reference.
+    targets: HashSet<String>,
+    /// Ids of the reference elements themselves, so backlinks can be dropped.
+    reference_ids: HashSet<String>,
+    /// Target ids in order of first reference during the transform.
+    order: Vec<String>,
+    /// Target id → footnote number (1-based).
+    numbers: HashMap<String, usize>,
+    /// Whether a footnote definition is being converted.
+    in_definition: bool,
+}
+
+/// Record every footnote reference in the document.
+pub(crate) fn index(state: &mut State, document: &Handle) {
+    index_inner(state, document, 0);
+}
+
+fn index_inner(state: &mut State, handle: &Handle, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if is_tag(handle, "a") {
+        if let Some(id) = reference_target(state, handle) {
+            state.footnotes.targets.insert(id);
+            for element in [Some(handle.clone()), parent_element(handle)]
+                .into_iter()
+                .flatten()
+            {
+                if let Some(ref_id) = get_attr(&element, "id") {
+                    state.footnotes.reference_ids.insert(ref_id);
+                }
+            }
+        }
+    }
+    for child in handle.children.borrow().iter() {
+        index_inner(state, child, depth + 1);
+    }
+}
+
+/// The id of the footnote `link` points to, if it is a footnote reference.
+fn reference_target(state: &State, link: &Handle) -> Option<String> {
+    let href = get_attr(link, "href")?;
+    let id = href.strip_prefix('#').filter(|id| !id.is_empty())?;
+    let target = state.element_by_id.get(id)?;
+
+    let explicit = get_attr(link, "role").as_deref() == Some("doc-noteref")
+        || has_class(link, "footnote-ref")
+        || has_class(link, "footnote-reference")
+        || has_attr(link, "data-footnote-ref")
+        || parent_element(link).is_some_and(|parent| {
+            is_tag(&parent, "sup")
+                && ["reference", "footnote-ref", "fn", "footnote"]
+                    .iter()
+                    .any(|class| has_class(&parent, class))
+        });
+    (explicit || is_definition(target)).then(|| id.to_string())
+}
+
+/// Whether `handle` looks like a footnote body.
+fn is_definition(handle: &Handle) -> bool {
+    is_marked_definition(handle) || ancestors(handle).any(|ancestor| is_container(&ancestor))
+}
+
+/// Whether `handle` is marked up as a footnote body in its own right.
+fn is_marked_definition(handle: &Handle) -> bool {
+    matches!(
+        get_attr(handle, "role").as_deref(),
+        Some("doc-footnote" | "doc-endnote")
+    ) || (is_tag(handle, "aside") && has_class(handle, "footnote"))
+}
+
+/// Whether `handle` is a list or section of footnotes.
+fn is_container(handle: &Handle) -> bool {
+    matches!(
+        get_attr(handle, "role").as_deref(),
+        Some("doc-endnotes" | "doc-footnotes")
+    ) || has_attr(handle, "data-footnotes")
+        || [
+            "footnotes",
+            "footnote-list",
+            "references",
+            "reflist",
+            "wp-block-footnotes",
+        ]
+        .iter()
+        .any(|class| has_class(handle, class))
+}
+
+fn ancestors(handle: &Handle) -> impl Iterator<Item = Handle> {
+    std::iter::successors(parent_element(handle), parent_element)
+}
+
+/// Convert a footnote reference link, numbering its target on first use.
+pub(crate) fn reference(state: &mut State, handle: &Handle) -> Option<mdast::Node> {
+    if state.footnotes.targets.is_empty() {
+        return None;
+    }
+    let id = get_attr(handle, "href")?.strip_prefix('#')?.to_string();
+    if !state.footnotes.targets.contains(&id) {
+        return None;
+    }
+    let next = state.footnotes.order.len() + 1;
+    let number = *state.footnotes.numbers.entry(id.clone()).or_insert(next);
+    if number == next {
+        state.footnotes.order.push(id);
+    }
+    Some(mdast::Node::FootnoteReference(mdast::FootnoteReference {
+        identifier: number.to_string(),
+        label: None,
+    }))
+}
+
+/// Whether `handle` should be dropped where it appears: a footnote body or
+/// a container holding only referenced bodies (both are emitted at the end),
+/// or a backlink inside a definition.
+pub(crate) fn is_suppressed(state: &State, handle: &Handle) -> bool {
+    let footnotes = &state.footnotes;
+    if footnotes.targets.is_empty() {
+        return false;
+    }
+    if footnotes.in_definition {
+        return is_backlink(footnotes, handle);
+    }
+    if get_attr(handle, "id").is_some_and(|id| footnotes.targets.contains(&id)) {
+        return true;
+    }
+    if is_container(handle) {
+        let mut ids = Vec::new();
+        collect_definition_ids(handle, &mut ids, 0);
+        return !ids.is_empty() && ids.iter().all(|id| footnotes.targets.contains(id));
+    }
+    false
+}
+
+fn collect_definition_ids(handle: &Handle, ids: &mut Vec<String>, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    for child in handle.children.borrow().iter() {
+        if !matches!(child.data, NodeData::Element { .. }) {
+            continue;
+        }
+        if is_tag(child, "li") || is_marked_definition(child) {
+            ids.push(get_attr(child, "id").unwrap_or_default());
+        } else {
+            collect_definition_ids(child, ids, depth + 1);
+        }
+    }
+}
+
+fn is_backlink(footnotes: &Footnotes, handle: &Handle) -> bool {
+    // Wikipedia's `^ a b` backlink group and Sphinx's `[1]` brackets.
+    if ["mw-cite-backlink", "footnote-back", "fn-bracket"]
+        .iter()
+        .any(|class| has_class(handle, class))
+    {
+        return true;
+    }
+    if !is_tag(handle, "a") {
+        return false;
+    }
+    get_attr(handle, "role").as_deref() == Some("doc-backlink")
+        || has_attr(handle, "data-footnote-backref")
+        || get_attr(handle, "href")
+            .and_then(|href| href.strip_prefix('#').map(str::to_string))
+            .is_some_and(|id| footnotes.reference_ids.contains(&id))
+}
+
+/// Convert the bodies of all referenced footnotes, in reference order.
+/// Footnotes referenced from inside other footnotes are picked up too.
+pub(crate) fn definitions(state: &mut State) -> Vec<mdast::Node> {
+    let mut definitions = Vec::new();
+    let mut i = 0;
+    while i < state.footnotes.order.len() {
+        let id = state.footnotes.order[i].clone();
+        i += 1;
+        let Some(target) = state.element_by_id.get(&id).cloned() else {
+            continue;
+        };
+        state.footnotes.in_definition = true;
+        let children = super::wrap::wrap(all(state, &target));
+        state.footnotes.in_definition = false;
+        definitions.push(mdast::Node::FootnoteDefinition(mdast::FootnoteDefinition {
+            identifier: i.to_string(),
+            label: None,
+            children,
+        }));
+    }
+    definitions
+}
@@ -0,0 +1,172 @@
+// In-page fragment links.
+//
+// Markdown renderers give headings generated ids (GitHub: lowercased text,
+// punctuation dropped, spaces as `-`, `-1`/`-2` suffixes for repeats), so the
+// HTML ids that `#fragment` links point at are gone. A pre-pass maps every id
+// to its heading (or the nearest preceding heading for anchors elsewhere);
+// headings record their slug as they are converted, and a final pass rewrites
+// the links.
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::handlers::get_attr;
+use super::{State, MAX_DEPTH};
+use crate::mdast::{self, Node};
+
+/// Fragment bookkeeping threaded through `State`.
+#[derive(Default)]
+pub(crate) struct Fragments {
+    /// Element id → the heading that stands for it.
+    headings: HashMap<String, Handle>,
+    /// Heading (by pointer) → the fragment it gets in the output.
+    targets: HashMap<usize, String>,
+    /// Slugs handed out so far.
+    slugger: Slugger,
+}
+
+/// Map every id in the document to its heading.
+pub(crate) fn index(state: &mut State, document: &Handle) {
+    let mut current = None;
+    index_inner(state, document, &mut current, 0);
+}
+
+fn index_inner(state: &mut State, handle: &Handle, current: &mut Option<Handle>, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if let NodeData::Element { ref name, .. } = handle.data {
+        if matches!(name.local.as_ref(), "h1" | "h2" | "h3" | "h4" | "h5" | "h6") {
+            *current = Some(handle.clone());
+        }
+        if let (Some(id), Some(heading)) = (get_attr(handle, "id"), current.as_ref()) {
+            // Same rule as `element_by_id`: the first element with an id wins.
+            let first = state
+                .element_by_id
+                .get(&id)
+                .is_some_and(|element| Rc::ptr_eq(element, handle));
+            if first {
+                state.fragments.headings.insert(id, heading.clone());
+            }
+        }
+    }
+    for child in handle.children.borrow().iter() {
+        index_inner(state, child, current, depth + 1);
+    }
+}
+
+/// Record the slug of a converted heading. A heading that keeps its own id
+/// as an attribute block is linked by that id instead.
+pub(crate) fn heading(state: &mut State, handle: &Handle, heading: &mdast::Heading) {
+    let slug = state.fragments.slugger.slug(&plain_text(&heading.children));
+    let target = heading.attributes.id.clone().unwrap_or(slug);
+    state
+        .fragments
+        .targets
+        .entry(Rc::as_ptr(handle) as usize)
+        .or_insert(target);
+}
+
+/// Whether `href` is an in-page link that [`rewrite`] will handle.
+pub(crate) fn is_rewritable(state: &State, href: &str) -> bool {
+    href.strip_prefix('#')
+        .is_some_and(|id| state.fragments.headings.contains_key(id))
+}
+
+/// Point `#id` links at the fragments of their headings.
+pub(crate) fn rewrite(state: &State, nodes: &mut [Node]) {
+    for node in nodes.iter_mut() {
+        if let Node::Link(link) = node {
+            if let Some(target) = link.url.strip_prefix('#').and_then(|id| target(state, id)) {
+                link.url = format!("#{target}");
+            }
+        }
+        if let Some(children) = node.children_mut() {
+            rewrite(state, children);
+        }
+    }
+}
+
+fn target<'a>(state: &'a State, id: &str) -> Option<&'a String> {
+    let heading = state.fragments.headings.get(id)?;
+    state.fragments.targets.get(&(Rc::as_ptr(heading) as usize))
+}
+
+/// Text content of a heading, as a renderer would see it.
+fn plain_text(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(&t.value),
+            Node::InlineCode(c) => text.push_str(&c.value),
+            Node::Break(_) => text.push(' '),
+            _ => {
+                if let Some(children) = node.children() {
+                    text.push_str(&plain_text(children));
+                }
+            }
+        }
+    }
+    text
+}
+
+/// GitHub-style slug generator (port of github-slugger).
+#[derive(Default)]
+pub(crate) struct Slugger {
+    /// Base slug → times it has been repeated.
+    occurrences: HashMap<String, usize>,
+    /// Every slug handed out, suffixed ones included.
+    used: HashSet<String>,
+}
+
+impl Slugger {
+    /// Slug for `text`, suffixed with `-1`, `-2`, … if already taken.
+    pub(crate) fn slug(&mut self, text: &str) -> String {
+        let base = slug(text);
+        let mut result = base.clone();
+        while self.used.contains(&result) {
+            let count = self.occurrences.entry(base.clone()).or_insert(0);
+            *count += 1;
+            result = format!("{base}-{count}");
+        }
+        self.used.insert(result.clone());
+        result
+    }
+}
+
+/// Lowercase, drop everything but letters, digits, `-`, `_` and spaces, and
+/// turn each space into `-`.
+fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter(|&c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_matches_github() {
+        assert_eq!(slug("Hello, World!"), "hello-world");
+        assert_eq!(slug("What's new in 2.0?"), "whats-new-in-20");
+        assert_eq!(slug("foo_bar  baz"), "foo_bar--baz");
+        assert_eq!(slug("Ünïcode Straße"), "ünïcode-straße");
+    }
+
+    #[test]
+    fn slugger_suffixes_repeats() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Intro"), "intro");
+        assert_eq!(slugger.slug("Intro"), "intro-1");
+        assert_eq!(slugger.slug("Intro 1"), "intro-1-1");
+        assert_eq!(slugger.slug("Intro"), "intro-2");
+        assert_eq!(slugger.slug(""), "");
+        assert_eq!(slugger.slug(""), "-1");
+    }
+}
@@ -0,0 +1,500 @@
+// Content-based code language guessing.
+//
+// A small heuristic classifier for code blocks with no language markup.
+// Unambiguous shapes are checked first (shebangs, JSON that parses, markup,
+// diffs, shell sessions, YAML); otherwise each language scores weighted
+// keyword and punctuation patterns and the confidence reflects both the
+// amount of evidence and the margin over the runner-up.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// A guessed code language.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LanguageGuess {
+    /// Fence language name, e.g. `"python"`.
+    pub language: &'static str,
+    /// Confidence between 0 and 1.
+    pub confidence: f32,
+}
+
+/// Guess the language of a code snippet from its text.
+///
+/// Returns `None` when nothing points at any language. Used by
+/// [`Options::language_guess_threshold`](crate::Options::language_guess_threshold)
+/// for code blocks without language markup.
+///
+/// ```
+/// use html2markdown::guess_language;
+///
+/// let guess = guess_language("#!/usr/bin/env python3\nprint('hi')").unwrap();
+/// assert_eq!(guess.language, "python");
+/// assert!(guess.confidence > 0.9);
+/// ```
+pub fn guess_language(code: &str) -> Option<LanguageGuess> {
+    let code = code.trim();
+    if code.is_empty() {
+        return None;
+    }
+    shape(code).or_else(|| keywords(code))
+}
+
+fn guess(language: &'static str, confidence: f32) -> Option<LanguageGuess> {
+    Some(LanguageGuess {
+        language,
+        confidence,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Shapes
+// ---------------------------------------------------------------------------
+
+static MARKUP_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"</?([a-zA-Z][\w:-]*)(\s[^<>]*)?/?>").unwrap());
+
+static YAML_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(- +)?([\w"'./-]+:(\s.*)?|- .*|-)$|^---$|^\.\.\.$"#).unwrap()
+});
+
+const HTML_TAGS: &[&str] = &[
+    "html", "head", "body", "div", "span", "p", "a", "ul", "ol", "li", "table", "tr", "td", "img",
+    "script", "style", "link", "meta", "section", "header", "footer", "nav", "form", "input",
+    "button", "h1", "h2", "h3", "br", "template",
+];
+
+/// Formats recognizable from their overall shape.
+fn shape(code: &str) -> Option<LanguageGuess> {
+    let first = code.lines().next().unwrap_or_default();
+
+    if let Some(interpreter) = first.strip_prefix("#!") {
+        if let Some(language) = shebang_language(interpreter) {
+            return guess(language, 0.99);
+        }
+    }
+    if code.starts_with("<?php") {
+        return guess("php", 0.99);
+    }
+    if (code.starts_with('{') || code.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(code).is_ok()
+    {
+        return guess("json", 0.95);
+    }
+    if code.starts_with("<?xml") {
+        return guess("xml", 0.95);
+    }
+    if code.starts_with('<') && code.ends_with('>') {
+        let lower = code.to_ascii_lowercase();
+        if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+            return guess("html", 0.95);
+        }
+        let tags: Vec<String> = MARKUP_TAG
+            .captures_iter(code)
+            .map(|c| c[1].to_ascii_lowercase())
+            .collect();
+        if tags.len() >= 2 {
+            let html = tags.iter().any(|t| HTML_TAGS.contains(&t.as_str()));
+            return if html {
+                guess("html", 0.85)
+            } else {
+                guess("xml", 0.7)
+            };
+        }
+    }
+    if code.lines().any(|l| l.starts_with("@@ -"))
+        || (code.lines().any(|l| l.starts_with("--- "))
+            && code.lines().any(|l| l.starts_with("+++ ")))
+    {
+        return guess("diff", 0.95);
+    }
+    if first.starts_with("$ ") {
+        return guess("console", 0.9);
+    }
+
+    let lines: Vec<&str> = code
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .collect();
+    if lines.len() >= 2
+        && lines.iter().all(|l| YAML_LINE.is_match(l))
+        && lines
+            .iter()
+            .any(|l| l.trim_end().ends_with(':') || l.contains(": "))
+    {
+        return guess("yaml", 0.8);
+    }
+    None
+}
+
+fn shebang_language(interpreter: &str) -> Option<&'static str> {
+    let mut words = interpreter.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    let program = if program == "env" {
+        words.find(|w| !w.starts_with('-'))?
+    } else {
+        program
+    };
+    let language = match program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "sh" | "bash" | "zsh" | "ksh" | "dash" => "bash",
+        "python" => "python",
+        "node" | "nodejs" => "javascript",
+        "deno" | "ts-node" => "typescript",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        "php" => "php",
+        "fish" => "fish",
+        "pwsh" => "powershell",
+        _ => return None,
+    };
+    Some(language)
+}
+
+// ---------------------------------------------------------------------------
+// Keywords
+// ---------------------------------------------------------------------------
+
+/// Weighted patterns for one language.
+type Patterns = (&'static str, Vec<(Regex, f32)>);
+
+/// `(language, [(pattern, weight)])`. Patterns are multi-line; each counts at
+/// most [`MAX_HITS`] times so one repeated construct cannot decide alone.
+static PATTERNS: LazyLock<Vec<Patterns>> = LazyLock::new(|| {
+    let table: &[(&str, &[(&str, f32)])] = &[
+        (
+            "rust",
+            &[
+                (r"\bfn\s+\w+\s*[<(]", 3.0),
+                (r"\blet\s+mut\b", 3.0),
+                (r"\bimpl\b", 2.0),
+                (r"\bpub\s+(fn|struct|enum|mod|use|trait)\b", 3.0),
+                (r"\b(println|vec|format|assert_eq)!", 3.0),
+                (r"^\s*use\s+\w+::", 3.0),
+                (r"&mut\s|&self\b|\bSome\(|\bOk\(", 2.0),
+                (r"#\[derive\(", 3.0),
+            ],
+        ),
+        (
+            "python",
+            &[
+                (r"^\s*def\s+\w+\(.*\)\s*(->.*)?:\s*$", 3.0),
+                (r"^\s*from\s+[\w.]+\s+import\s", 3.0),
+                (r"^\s*import\s+[\w.]+(\s+as\s+\w+)?\s*$", 1.5),
+                (r"^\s*class\s+\w+(\(.*\))?:\s*$", 3.0),
+                (r"\bself\.\w", 2.0),
+                (
+                    r"^\s*(if|elif|else|for|while|with|try|except|finally)\b.*:\s*$",
+                    1.0,
+                ),
+                (r"\bprint\(", 1.0),
+                (r"\b(None|True|False)\b", 0.5),
+                (r"__\w+__", 2.0),
+            ],
+        ),
+        (
+            "javascript",
+            &[
+                (r"\b(const|let|var)\s+\w+\s*=", 1.5),
+                (r"\bfunction\s*\w*\s*\(", 2.0),
+                (r"=>", 1.0),
+                (r"\bconsole\.\w+\(", 3.0),
+                (r"\brequire\(['\x22]", 3.0),
+                (r"\b(document|window)\.\w", 2.0),
+                (r"^\s*import\s.*\sfrom\s+['\x22]", 2.0),
+                (r"^\s*export\s+(default|const|function|class)\b", 2.0),
+                (r"===|!==", 2.0),
+            ],
+        ),
+        (
+            "typescript",
+            &[
+                (r"\w\??:\s*(string|number|boolean|any|void|unknown)\b", 2.5),
+                (r"^\s*(export\s+)?interface\s+\w+(<.*>)?\s*\{", 2.5),
+                (r"^\s*(export\s+)?type\s+\w+(<.*>)?\s*=", 2.5),
+                (r"\b(private|public|readonly)\s+\w+\s*:", 2.0),
+            ],
+        ),
+        (
+            "go",
+            &[
+                (r"^package\s+\w+\s*$", 3.0),
+                (r"\bfunc\s+(\(\w+\s+\*?\w+\)\s*)?\w+\(", 3.0),
+                (r":=", 1.5),
+                (r"\bfmt\.\w+\(", 3.0),
+                (r"\bgo\s+func\b|\bchan\b|\bdefer\b", 2.0),
+                (r"^import\s+\(", 3.0),
+                (r"\berr\s*!=\s*nil\b", 3.0),
+            ],
+        ),
+        (
+            "java",
+            &[
+                (
+                    r"\bpublic\s+(static\s+)?(final\s+)?(class|void|int|String)\b",
+                    2.0,
+                ),
+                (r"System\.out\.print", 3.0),
+                (r"@Override\b", 3.0),
+                (r"^\s*import\s+java\.", 3.0),
+                (r"String\[\]\s+args", 3.0),
+                (r"\bprivate\s+(final\s+)?[A-Z]\w*(<.*>)?\s+\w+\s*[;=]", 1.5),
+            ],
+        ),
+        (
+            "csharp",
+            &[
+                (r"^\s*using\s+System", 3.0),
+                (r"\bConsole\.Write", 3.0),
+                (r"\{\s*get;\s*(set;\s*)?\}", 3.0),
+                (r"\bvar\s+\w+\s*=\s*new\b", 1.5),
+                (r"^\s*namespace\s+[\w.]+", 1.5),
+                (r"\basync\s+Task\b", 3.0),
+            ],
+        ),
+        (
+            "c",
+            &[
+                (r"^\s*#include\s*<\w+\.h>", 3.0),
+                (r"\bprintf\(", 2.0),
+                (r"\bint\s+main\s*\(", 2.0),
+                (r"\b(malloc|free|sizeof)\b", 2.0),
+                (r"\bstruct\s+\w+\s*\{", 1.0),
+                (r"^\s*#define\s", 1.5),
+            ],
+        ),
+        (
+            "cpp",
+            &[
+                (r"^\s*#include\s*<\w+>", 3.0),
+                (r"\bstd::", 3.0),
+                (r"\b(cout|cerr)\s*<<|\bcin\s*>>", 3.0),
+                (r"\btemplate\s*<", 3.0),
+                (r"\bnullptr\b", 2.0),
+                (r"^\s*using\s+namespace\s", 2.0),
+            ],
+        ),
+        (
+            "ruby",
+            &[
+                (r"^\s*def\s+\w+[?!]?(\(.*\))?\s*$", 2.0),
+                (r"^\s*end\s*$", 2.0),
+                (r"\bputs\b", 2.0),
+                (r"^\s*require\s+['\x22]", 2.0),
+                (r"\bdo\s*(\|[^|]*\|)?\s*$", 2.0),
+                (r"\battr_(accessor|reader|writer)\b", 3.0),
+                (r"\.each\b", 1.5),
+            ],
+        ),
+        (
+            "php",
+            &[
+                (r"\$this->", 3.0),
+                (r"\bfunction\s+\w+\s*\(\$", 3.0),
+                (r"\$\w+\s*=\s*[^=]", 1.0),
+                (r"\b(echo|namespace|use)\s+[\w\\$]", 0.5),
+            ],
+        ),
+        (
+            "bash",
+            &[
+                (r"^\s*(if|while|until)\s+\[\[?\s", 2.0),
+                (r"^\s*(fi|done|esac)\s*$", 3.0),
+                (r"\becho\s+[\x22$]", 1.5),
+                (r"\$\{\w+\}|\$\(\w", 1.5),
+                (r"^\s*export\s+\w+=", 2.0),
+                (
+                    r"^\s*(sudo|apt|apt-get|brew|npm|npx|pip|cargo|git|cd|mkdir|curl|wget|docker|yarn|chmod)\s",
+                    1.5,
+                ),
+                (r"\s&&\s", 0.5),
+            ],
+        ),
+        (
+            "sql",
+            &[
+                (r"(?i)\bselect\b[\s\S]+?\bfrom\b", 3.0),
+                (r"(?i)\binsert\s+into\b", 3.0),
+                (r"(?i)\bcreate\s+(table|index|view|database)\b", 3.0),
+                (r"(?i)\bupdate\s+\w+\s+set\b", 3.0),
+                (r"(?i)\bdelete\s+from\b", 3.0),
+                (r"(?i)\b(inner|left|right)?\s*join\b[\s\S]+?\bon\b", 1.0),
+                (r"(?i)\bwhere\b", 0.5),
+            ],
+        ),
+        (
+            "css",
+            &[
+                (r"^\s*[.#]?[\w-]+([\s,>+~]*[.#:]?[\w-]+)*\s*\{\s*$", 2.0),
+                (r"^\s*[a-z-]+\s*:\s*[^;{}]+;\s*$", 1.0),
+                (r"@(media|import|keyframes|font-face)\b", 3.0),
+                (r"\b\d+(px|em|rem|vh|vw)\b", 1.5),
+                (r"#[0-9a-fA-F]{3,6}\b", 1.0),
+            ],
+        ),
+        (
+            "toml",
+            &[
+                (r"^\s*\[\[?[\w.\x22-]+\]\]?\s*$", 1.5),
+                (r"^\s*[\w.-]+\s*=\s*(\x22|'|\d|\[|\{|true\b|false\b)", 1.5),
+            ],
+        ),
+    ];
+    table
+        .iter()
+        .map(|&(language, patterns)| {
+            let patterns = patterns
+                .iter()
+                .map(|&(pattern, weight)| (Regex::new(&format!("(?m){pattern}")).unwrap(), weight))
+                .collect();
+            (language, patterns)
+        })
+        .collect()
+});
+
+/// Times a single pattern may contribute to a score.
+const MAX_HITS: usize = 3;
+
+/// Evidence needed for roughly two-thirds confidence.
+const EVIDENCE_SCALE: f32 = 4.0;
+
+fn keywords(code: &str) -> Option<LanguageGuess> {
+    let mut scores: Vec<(&'static str, f32)> = PATTERNS
+        .iter()
+        .map(|(language, patterns)| {
+            let score = patterns
+                .iter()
+                .map(|(regex, weight)| regex.find_iter(code).take(MAX_HITS).count() as f32 * weight)
+                .sum();
+            (*language, score)
+        })
+        .collect();
+
+    // TypeScript is JavaScript plus types: add the JavaScript evidence once a
+    // TypeScript-only construct is present.
+    let javascript = score_of(&scores, "javascript");
+    if let Some(typescript) = scores.iter_mut().find(|(l, _)| *l == "typescript") {
+        if typescript.1 > 0.0 {
+            typescript.1 += javascript;
+        }
+    }
+    punctuation(code, &mut scores);
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (language, best) = scores[0];
+    if best <= 0.0 {
+        return None;
+    }
+    let second = scores[1].1.max(0.0);
+    let evidence = 1.0 - (-best / EVIDENCE_SCALE).exp();
+    let margin = (best - second) / best;
+    guess(language, evidence * margin)
+}
+
+fn score_of(scores: &[(&'static str, f32)], language: &str) -> f32 {
+    scores
+        .iter()
+        .find(|(l, _)| *l == language)
+        .map_or(0.0, |(_, s)| *s)
+}
+
+/// Line-ending profile: `;`-terminated lines favour C-family languages and
+/// against Python/Ruby; `:`-terminated lines favour Python.
+fn punctuation(code: &str, scores: &mut [(&'static str, f32)]) {
+    let lines: Vec<&str> = code
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let ratio = |suffix: char| {
+        lines.iter().filter(|l| l.ends_with(suffix)).count() as f32 / lines.len() as f32
+    };
+    let semicolons = ratio(';');
+    let colons = ratio(':');
+    for (language, score) in scores.iter_mut() {
+        if *score <= 0.0 {
+            continue;
+        }
+        match *language {
+            "python" | "ruby" | "yaml" => *score -= 3.0 * semicolons,
+            "c" | "cpp" | "java" | "csharp" | "javascript" | "typescript" | "php" | "rust" => {
+                *score += semicolons
+            }
+            _ => {}
+        }
+        if *language == "python" {
+            *score += 2.0 * colons;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(code: &str) -> Option<&'static str> {
+        guess_language(code).map(|g| g.language)
+    }
+
+    #[test]
+    fn shapes() {
+        assert_eq!(language("#!/bin/bash\necho hi"), Some("bash"));
+        assert_eq!(language("#!/usr/bin/env -S python3 -u\n"), Some("python"));
+        assert_eq!(language(r#"{"a": [1, 2]}"#), Some("json"));
+        assert_eq!(language("<div>\n  <p>Hi</p>\n</div>"), Some("html"));
+        assert_eq!(language("<note><to>A</to></note>"), Some("xml"));
+        assert_eq!(language("$ cargo build\n   Compiling x"), Some("console"));
+        assert_eq!(language("name: x\nitems:\n  - a\n  - b"), Some("yaml"));
+        assert_eq!(
+            language("--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b"),
+            Some("diff")
+        );
+    }
+
+    #[test]
+    fn keywords() {
+        assert_eq!(
+            language("fn main() {\n    let mut x = vec![1];\n    println!(\"{x:?}\");\n}"),
+            Some("rust")
+        );
+        assert_eq!(
+            language("def greet(name):\n    if name:\n        print(name)"),
+            Some("python")
+        );
+        assert_eq!(
+            language("const x = require('x');\nconsole.log(x === 1);"),
+            Some("javascript")
+        );
+        assert_eq!(
+            language("interface User {\n  name: string;\n}\nconst u: User = { name: 'a' };"),
+            Some("typescript")
+        );
+        assert_eq!(
+            language("package main\n\nfunc main() {\n\tfmt.Println(\"hi\")\n}"),
+            Some("go")
+        );
+        assert_eq!(
+            language("SELECT id, name\nFROM users\nWHERE id = 1;"),
+            Some("sql")
+        );
+        assert_eq!(
+            language(".btn {\n  color: #fff;\n  padding: 4px;\n}"),
+            Some("css")
+        );
+        assert_eq!(
+            language("#include <stdio.h>\nint main(void) {\n  printf(\"hi\");\n}"),
+            Some("c")
+        );
+    }
+
+    #[test]
+    fn prose_is_not_code() {
+        assert_eq!(language("Hello there, this is just text."), None);
+        assert_eq!(language(""), None);
+    }
+
+    #[test]
+    fn weak_evidence_has_low_confidence() {
+        let guess = guess_language("x = 1").map_or(0.0, |g| g.confidence);
+        assert!(guess < 0.5, "{guess}");
+    }
+}
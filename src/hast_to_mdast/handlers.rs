@@ -6,6 +6,7 @@
 
 use markup5ever_rcdom::{Handle, NodeData};
 
+use super::custom::{Element, TransformContext};
 use super::util::{drop_surrounding_breaks, is_whitespace_only};
 use super::State;
 use crate::mdast;
@@ -56,6 +57,8 @@ fn one_inner(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
             };
             if text.is_empty() {
                 vec![]
+            } else if state.options.math && !state.in_pre && text.contains("\\(") {
+                super::math::split_inline(&text)
             } else {
                 vec![mdast::Node::Text(mdast::Text { value: text })]
             }
@@ -78,6 +81,32 @@ fn one_inner(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
             {
                 return vec![];
             }
+            // So do elements matching an exclude selector.
+            if state
+                .options
+                .exclude_selectors
+                .iter()
+                .any(|selector| selector.matches(handle))
+            {
+                return vec![];
+            }
+            // Copy-to-clipboard buttons next to code blocks are page chrome.
+            if super::code::is_code_copy_button(state, handle) {
+                return vec![];
+            }
+            // Code captions are moved into the code block's meta.
+            if state.options.code_meta && super::code::is_code_caption(state, handle) {
+                return vec![];
+            }
+            // Footnote bodies are emitted at the end of the document.
+            if super::footnotes::is_suppressed(state, handle) {
+                return vec![];
+            }
+            // Custom handlers take precedence over the built-in dispatch.
+            if let Some(entry) = state.handlers.find(handle) {
+                let mut cx = TransformContext { state };
+                return entry.handler.handle(&mut cx, &Element::new(handle));
+            }
             dispatch_element(state, handle, tag)
         }
         NodeData::Document => all(state, handle),
@@ -90,8 +119,33 @@ fn one_inner(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
 // ---------------------------------------------------------------------------
 
 /// Route an element to its handler based on tag name.
-fn dispatch_element(state: &mut State, handle: &Handle, tag: &str) -> Vec<mdast::Node> {
+pub(crate) fn dispatch_element(state: &mut State, handle: &Handle, tag: &str) -> Vec<mdast::Node> {
     match tag {
+        // Kept as raw HTML around the converted children, if requested.
+        _ if state.options.keep_html.iter().any(|kept| kept == tag) => {
+            handle_kept_html(state, handle, tag)
+        }
+
+        // MathML, KaTeX and MathJax → math, if enabled.
+        _ if state.options.math && super::math::is_math(handle, tag) => {
+            super::math::handle(state, handle, tag)
+        }
+
+        // Notes, tips and warnings → admonitions, if enabled.
+        "div" | "aside" | "section" | "blockquote" if state.options.admonitions => {
+            match super::admonitions::kind(handle, tag, &state.options.admonition_kinds) {
+                Some(kind) => super::admonitions::handle(state, handle, kind),
+                None if tag == "blockquote" => handle_blockquote(state, handle),
+                None => super::wrap::wrap(all(state, handle)),
+            }
+        }
+
+        // Extended inline syntax, if enabled.
+        "mark" if state.options.highlight => handle_extension(state, handle, tag),
+        "sup" if state.options.superscript => handle_extension(state, handle, tag),
+        "sub" if state.options.subscript => handle_extension(state, handle, tag),
+        "ins" | "u" if state.options.insert => handle_extension(state, handle, tag),
+
         // Ignore — return nothing
         "applet" | "area" | "basefont" | "bgsound" | "caption" | "col" | "colgroup" | "command"
         | "content" | "datalist" | "dialog" | "element" | "embed" | "frame" | "frameset"
@@ -192,6 +246,17 @@ pub(crate) fn has_attr(handle: &Handle, name: &str) -> bool {
     }
 }
 
+/// Check whether the element's `class` attribute contains `class` as a whole word.
+pub(crate) fn has_class(handle: &Handle, class: &str) -> bool {
+    if let NodeData::Element { ref attrs, .. } = handle.data {
+        attrs.borrow().iter().any(|a| {
+            a.name.local.as_ref() == "class" && a.value.split_whitespace().any(|c| c == class)
+        })
+    } else {
+        false
+    }
+}
+
 /// Check whether the element's tag name matches the given name.
 pub(crate) fn is_tag(handle: &Handle, expected: &str) -> bool {
     if let NodeData::Element { ref name, .. } = handle.data {
@@ -255,9 +320,14 @@ fn collapse_whitespace_preserving_newlines(s: &str) -> String {
 
 /// Extract the text content from all descendants of an element (for <pre> blocks).
 /// Port of hast-util-to-text behavior: block elements get newlines around them, <br> becomes \n.
-fn to_text(handle: &Handle) -> String {
+pub(crate) fn to_text(handle: &Handle) -> String {
+    to_text_without(handle, |_| false)
+}
+
+/// Like [`to_text`], leaving out descendants for which `skip` returns true.
+pub(crate) fn to_text_without(handle: &Handle, skip: fn(&Handle) -> bool) -> String {
     let mut result = String::new();
-    collect_text(handle, &mut result);
+    collect_text(handle, &mut result, skip);
     result
 }
 
@@ -280,7 +350,7 @@ fn collect_table_rows(handle: &Handle, rows: &mut Vec<String>) {
                     if let NodeData::Element { ref name, .. } = child.data {
                         if matches!(name.local.as_ref(), "td" | "th") {
                             let mut cell_text = String::new();
-                            collect_text(child, &mut cell_text);
+                            collect_text(child, &mut cell_text, |_| false);
                             cells.push(cell_text.trim().to_string());
                         }
                     }
@@ -302,12 +372,15 @@ fn collect_table_rows(handle: &Handle, rows: &mut Vec<String>) {
     }
 }
 
-fn collect_text(handle: &Handle, result: &mut String) {
+fn collect_text(handle: &Handle, result: &mut String, skip: fn(&Handle) -> bool) {
     match &handle.data {
         NodeData::Text { ref contents } => {
             result.push_str(&contents.borrow());
         }
         NodeData::Element { ref name, .. } => {
+            if skip(handle) {
+                return;
+            }
             let tag = name.local.as_ref();
             // <br> → newline
             if tag == "br" {
@@ -322,7 +395,7 @@ fn collect_text(handle: &Handle, result: &mut String) {
                 }
                 let start_len = result.len();
                 for child in handle.children.borrow().iter() {
-                    collect_text(child, result);
+                    collect_text(child, result, skip);
                 }
                 // Add trailing \n if content was added and doesn't end with \n.
                 if result.len() > start_len && !result.ends_with('\n') {
@@ -330,13 +403,13 @@ fn collect_text(handle: &Handle, result: &mut String) {
                 }
             } else {
                 for child in handle.children.borrow().iter() {
-                    collect_text(child, result);
+                    collect_text(child, result, skip);
                 }
             }
         }
         NodeData::Document => {
             for child in handle.children.borrow().iter() {
-                collect_text(child, result);
+                collect_text(child, result, skip);
             }
         }
         _ => {}
@@ -429,8 +502,16 @@ fn trim_trailing_lines(s: &str) -> &str {
 /// <a> → Link
 /// Port of hast-util-to-mdast/lib/handlers/a.js
 fn handle_a(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
+    if let Some(reference) = super::footnotes::reference(state, handle) {
+        return vec![reference];
+    }
     let href = get_attr(handle, "href").unwrap_or_default();
-    let url = state.resolve(&href);
+    // Kept relative so `fragments::rewrite` can find it after `<base>`.
+    let url = if super::fragments::is_rewritable(state, &href) {
+        href
+    } else {
+        state.resolve(&href)
+    };
     let title = get_attr(handle, "title");
     let children = all(state, handle);
 
@@ -492,52 +573,65 @@ fn handle_code_inline(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
 /// <pre>, <listing>, <xmp>, <plaintext> → Code
 /// Port of hast-util-to-mdast/lib/handlers/code.js
 fn handle_code_block(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
-    // Extract language from <code class="language-*"> child.
+    code_block(state, handle, handle)
+}
+
+/// Build a `Code` node from `handle`; `outer` is the element whose wrappers
+/// are searched for a language (the `<pre>`, or its line-number table).
+fn code_block(state: &mut State, handle: &Handle, outer: &Handle) -> Vec<mdast::Node> {
+    // Extract language from classes/attributes on <code>, <pre> or wrappers.
     let lang = if is_tag(handle, "pre") {
-        find_code_language(handle)
+        super::code::find_code_language(state, handle, outer)
     } else {
         None
     };
 
-    // Get raw text content (preserving whitespace).
+    // Get raw text content (preserving whitespace), without line numbers
+    // and copy buttons.
     let old_in_pre = state.in_pre;
     state.in_pre = true;
-    let value = to_text(handle);
+    let value = super::code::code_text(state, handle);
     state.in_pre = old_in_pre;
 
-    let value = trim_trailing_lines(&value).to_string();
-
-    vec![mdast::Node::Code(mdast::Code {
-        value,
-        lang,
-        meta: None,
-    })]
-}
+    let mut value = trim_trailing_lines(&value).to_string();
+    if state.options.code_dedent {
+        value = super::code::dedent(&value);
+    }
 
-/// Find the `language-*` class on a `<code>` child of `<pre>`.
-fn find_code_language(pre_handle: &Handle) -> Option<String> {
-    for child in pre_handle.children.borrow().iter() {
-        if let NodeData::Element {
-            ref name,
-            ref attrs,
-            ..
-        } = child.data
-        {
-            if name.local.as_ref() == "code" {
-                for attr in attrs.borrow().iter() {
-                    if attr.name.local.as_ref() == "class" {
-                        let class_val = attr.value.to_string();
-                        for class in class_val.split_whitespace() {
-                            if let Some(lang) = class.strip_prefix("language-") {
-                                return Some(lang.to_string());
-                            }
-                        }
-                    }
-                }
-            }
+    // A language aliased to "" is final: the block is not guessed.
+    let lang = lang
+        .or_else(|| {
+            let threshold = state.options.language_guess_threshold?;
+            super::guess::guess_language(&value)
+                .filter(|guess| guess.confidence >= threshold)
+                .map(|guess| super::code::alias_language(state, guess.language.to_string()))
+        })
+        .filter(|lang| !lang.is_empty());
+
+    // Stripped prompts are recorded in the meta, which needs a language:
+    // blocks without one become `console` or `pycon`.
+    let mut prompt = None;
+    let mut lang = lang;
+    if state.options.code_strip_prompts {
+        if let Some(stripped) = super::code::strip_prompts(&value) {
+            value = stripped.code;
+            prompt = Some(stripped.prompt);
+            lang = lang.or_else(|| Some(stripped.language.to_string()));
         }
     }
-    None
+
+    // Meta follows the language; without one, its first word would be read
+    // as the language.
+    let mut meta = Vec::new();
+    if lang.is_some() && state.options.code_meta {
+        meta = super::code::code_meta(state, handle, outer);
+    }
+    if let Some(prompt) = prompt {
+        meta.push(format!("prompt=\"{prompt}\""));
+    }
+    let meta = (!meta.is_empty()).then(|| meta.join(" "));
+
+    vec![mdast::Node::Code(mdast::Code { value, lang, meta })]
 }
 
 /// <del>, <s>, <strike> → Delete
@@ -564,6 +658,10 @@ fn handle_dl(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
         clean.push(child.clone());
     }
 
+    if state.options.definition_lists && starts_with_term(&clean) {
+        return definition_list(state, &clean);
+    }
+
     // Group titles (dt) and definitions (dd).
     struct Group {
         titles: Vec<Handle>,
@@ -636,6 +734,84 @@ fn handle_dl(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
     })]
 }
 
+/// Whether the first content of a `<dl>` is a non-empty `<dt>`: the
+/// definition-list syntax has no way to write a description without a term.
+fn starts_with_term(children: &[Handle]) -> bool {
+    let first = children.iter().find(|child| match &child.data {
+        NodeData::Text { contents } => !contents.borrow().trim().is_empty(),
+        NodeData::Element { .. } => true,
+        _ => false,
+    });
+    first.is_some_and(|dt| is_tag(dt, "dt") && !to_text(dt).trim().is_empty())
+}
+
+/// <dl> → DefinitionList. Content other than `<dt>` and `<dd>` joins the
+/// preceding description.
+fn definition_list(state: &mut State, children: &[Handle]) -> Vec<mdast::Node> {
+    let mut items: Vec<mdast::Node> = Vec::new();
+    for child in children {
+        if is_tag(child, "dt") {
+            let phrasing = term_phrasing(all(state, child));
+            if !is_whitespace_only(&phrasing) {
+                items.push(mdast::Node::DefinitionTerm(mdast::DefinitionTerm {
+                    children: phrasing,
+                }));
+            }
+            continue;
+        }
+        let is_dd = is_tag(child, "dd");
+        let nodes = if is_dd {
+            all(state, child)
+        } else {
+            one(state, child)
+        };
+        if is_whitespace_only(&nodes) {
+            continue;
+        }
+        let blocks = super::wrap::wrap(nodes);
+        match items.last_mut() {
+            Some(mdast::Node::DefinitionDescription(description)) if !is_dd => {
+                description.children.extend(blocks);
+            }
+            _ => items.push(mdast::Node::DefinitionDescription(
+                mdast::DefinitionDescription { children: blocks },
+            )),
+        }
+    }
+    if items.is_empty() {
+        return vec![];
+    }
+    vec![mdast::Node::DefinitionList(mdast::DefinitionList {
+        children: items,
+    })]
+}
+
+/// A term is a single line: block content in a `<dt>` is flattened to its
+/// phrasing, blocks and breaks separated by spaces.
+fn term_phrasing(nodes: Vec<mdast::Node>) -> Vec<mdast::Node> {
+    let space = || {
+        mdast::Node::Text(mdast::Text {
+            value: " ".to_string(),
+        })
+    };
+    let mut phrasing: Vec<mdast::Node> = Vec::new();
+    for node in nodes {
+        match node {
+            mdast::Node::Break(_) => phrasing.push(space()),
+            node if node.is_phrasing() => phrasing.push(node),
+            mut node => {
+                if let Some(children) = node.children_mut() {
+                    if !phrasing.is_empty() {
+                        phrasing.push(space());
+                    }
+                    phrasing.extend(term_phrasing(std::mem::take(children)));
+                }
+            }
+        }
+    }
+    phrasing
+}
+
 /// Convert a set of dt or dd handles to flow content for a dl list item.
 /// Port of hast-util-to-mdast/lib/handlers/dl.js `handle()` function.
 fn dl_handle_group(state: &mut State, handles: &[Handle]) -> Vec<mdast::Node> {
@@ -682,13 +858,81 @@ fn handle_em(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
     vec![mdast::Node::Emphasis(mdast::Emphasis { children })]
 }
 
+/// <mark>, <sup>, <sub>, <ins>, <u> → Highlight, Superscript, Subscript, Insert
+fn handle_extension(state: &mut State, handle: &Handle, tag: &str) -> Vec<mdast::Node> {
+    let children = all(state, handle);
+    if is_whitespace_only(&children) {
+        return children;
+    }
+    let node = match tag {
+        "mark" => mdast::Node::Highlight(mdast::Highlight { children }),
+        "sup" => mdast::Node::Superscript(mdast::Superscript { children }),
+        "sub" => mdast::Node::Subscript(mdast::Subscript { children }),
+        _ => mdast::Node::Insert(mdast::Insert { children }),
+    };
+    vec![node]
+}
+
 /// <h1>–<h6> → Heading
 /// Port of hast-util-to-mdast/lib/handlers/heading.js
 fn handle_heading(state: &mut State, handle: &Handle, tag: &str) -> Vec<mdast::Node> {
     let depth = tag.chars().nth(1).and_then(|c| c.to_digit(10)).unwrap_or(1) as u8;
-    let children = all(state, handle);
+    let mut children = all(state, handle);
+    let attributes = element_attributes(state, handle);
+    if attributes.id.is_some() {
+        // The empty target anchor is now redundant with the `{#id}`.
+        children.retain(
+            |child| !matches!(child, mdast::Node::Link(l) if l.url.is_empty() && l.children.is_empty()),
+        );
+    }
     let children = drop_surrounding_breaks(children);
-    vec![mdast::Node::Heading(mdast::Heading { depth, children })]
+    let heading = mdast::Heading {
+        depth,
+        children,
+        attributes,
+    };
+    if state.options.rewrite_fragment_links {
+        super::fragments::heading(state, handle, &heading);
+    }
+    vec![mdast::Node::Heading(heading)]
+}
+
+/// The `id` (and, if requested, classes) of `handle` as an attribute block.
+///
+/// Falls back to the id or name of an empty anchor inside the element
+/// (`<h2><a id="x"></a>Title</h2>`), a common way to mark heading targets.
+/// Values that cannot be written inside `{…}` are dropped.
+fn element_attributes(state: &State, handle: &Handle) -> mdast::Attributes {
+    let mode = state.options.keep_attributes;
+    if mode == super::KeepAttributes::None {
+        return mdast::Attributes::default();
+    }
+    let valid = |value: &str| {
+        !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || c == '{' || c == '}')
+    };
+    let id = get_attr(handle, "id")
+        .filter(|id| valid(id))
+        .or_else(|| empty_anchor_id(handle).filter(|id| valid(id)));
+    let classes = if mode == super::KeepAttributes::IdAndClasses {
+        get_attr(handle, "class")
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .filter(|class| valid(class))
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    mdast::Attributes { id, classes }
+}
+
+fn empty_anchor_id(handle: &Handle) -> Option<String> {
+    handle.children.borrow().iter().find_map(|child| {
+        if !is_tag(child, "a") || !to_text(child).trim().is_empty() {
+            return None;
+        }
+        get_attr(child, "id").or_else(|| get_attr(child, "name"))
+    })
 }
 
 /// <hr> → ThematicBreak
@@ -1161,6 +1405,12 @@ fn handle_p(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
     if children.is_empty() || is_whitespace_only(&children) {
         return vec![];
     }
+    if children
+        .iter()
+        .any(|child| matches!(child, mdast::Node::Math(_)))
+    {
+        return super::math::paragraph_math(children);
+    }
     vec![mdast::Node::Paragraph(mdast::Paragraph { children })]
 }
 
@@ -1379,6 +1629,33 @@ fn handle_strong(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
 /// <table> → Table (or Text if nested)
 /// Port of hast-util-to-mdast/lib/handlers/table.js
 fn handle_table(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
+    // Line-number gutter + code layout → the code alone.
+    if let Some(pre) = super::code::gutter_table_code(state, handle) {
+        return code_block(state, &pre, handle);
+    }
+
+    // Layout table → its cells' content as ordinary flow, if requested.
+    if state.options.unwrap_layout_tables && !state.in_table && super::tables::is_layout(handle) {
+        let mut result = Vec::new();
+        for row in super::tables::rows(handle) {
+            for cell in super::tables::cells(&row) {
+                result.extend(super::wrap::wrap(all(state, &cell)));
+            }
+        }
+        return result;
+    }
+
+    // Tables GFM cannot represent → cleaned-up HTML, if requested (unless
+    // tables are being collected as data).
+    if state.options.table_strategy == super::tables::TableStrategy::HtmlFallback
+        && !state.in_table
+        && state.tables.is_none()
+        && !super::tables::losses(handle).is_empty()
+    {
+        let value = super::tables::to_html(state, handle);
+        return vec![mdast::Node::Html(mdast::Html { value })];
+    }
+
     // Nested table → serialize as text using tab/newline separators.
     if state.in_table {
         let text = to_table_text(handle);
@@ -1388,10 +1665,19 @@ fn handle_table(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
     let old_in_table = state.in_table;
     state.in_table = true;
 
-    let (align, headless) = inspect_table(handle);
+    let (align, mut headless) = inspect_table(handle);
     let raw_nodes = all(state, handle);
     let mut rows = to_specific_table_rows(raw_nodes);
 
+    // A bold first row stands in for a missing header, if requested.
+    let promote = headless
+        && state.options.table_promote_header
+        && rows.len() > 1
+        && super::tables::has_header_row(handle);
+    if promote {
+        headless = false;
+    }
+
     // Add an empty header row if headless.
     if headless {
         rows.insert(
@@ -1408,6 +1694,15 @@ fn handle_table(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
         }
     }
 
+    // Header cells are bold already.
+    if let (true, Some(mdast::Node::TableRow(tr))) = (promote, rows.first_mut()) {
+        for cell in &mut tr.children {
+            if let mdast::Node::TableCell(tc) = cell {
+                tc.children = super::tables::unbold(std::mem::take(&mut tc.children));
+            }
+        }
+    }
+
     // Handle colspan/rowspan expansion.
     let mut columns = 1usize;
 
@@ -1447,6 +1742,15 @@ fn handle_table(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
         };
 
         for (cell_index, colspan, rowspan) in cells {
+            // Covered slots stay empty, or repeat the spanning cell.
+            let fill = match &rows[row_index] {
+                mdast::Node::TableRow(tr) if state.options.table_duplicate_spans => tr.children
+                    [cell_index]
+                    .children()
+                    .map(<[mdast::Node]>::to_vec)
+                    .unwrap_or_default(),
+                _ => vec![],
+            };
             let end_row = (row_index + rowspan as usize).min(row_count);
             for (span_offset, row) in rows[row_index..end_row].iter_mut().enumerate() {
                 let other_row_index = row_index + span_offset;
@@ -1458,7 +1762,7 @@ fn handle_table(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
                 let col_end = cell_index + colspan as usize;
                 if col_start < col_end {
                     let empty_cells: Vec<mdast::Node> = (col_start..col_end)
-                        .map(|_| mdast::Node::TableCell(mdast::TableCell::new(vec![])))
+                        .map(|_| mdast::Node::TableCell(mdast::TableCell::new(fill.clone())))
                         .collect();
                     if let mdast::Node::TableRow(tr) = row {
                         let insert_at = col_start.min(tr.children.len());
@@ -1509,10 +1813,55 @@ fn handle_table(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
 
     state.in_table = old_in_table;
 
-    vec![mdast::Node::Table(mdast::Table {
+    let table = mdast::Table {
         align,
         children: rows,
-    })]
+    };
+    if let Some(tables) = state.tables.as_mut() {
+        tables.push(super::table_data::from_table(handle, &table, headless));
+    }
+    if let Some(losses) = state.table_losses.as_mut() {
+        losses.push(super::tables::losses(handle));
+    }
+    let mut result = table_caption(state, handle);
+    result.push(mdast::Node::Table(table));
+    result
+}
+
+/// The `<caption>` of `table` as a paragraph, if captions are kept.
+fn table_caption(state: &mut State, table: &Handle) -> Vec<mdast::Node> {
+    use super::tables::TableCaption;
+    if state.options.table_caption == TableCaption::Drop {
+        return vec![];
+    }
+    let caption = table
+        .children
+        .borrow()
+        .iter()
+        .find(|child| is_tag(child, "caption"))
+        .cloned();
+    let Some(caption) = caption else {
+        return vec![];
+    };
+    // Written as one paragraph: paragraphs inside are unwrapped, other
+    // blocks dropped.
+    let children: Vec<mdast::Node> = all(state, &caption)
+        .into_iter()
+        .flat_map(|node| match node {
+            mdast::Node::Paragraph(p) => p.children,
+            other => vec![other],
+        })
+        .filter(mdast::Node::is_phrasing)
+        .collect();
+    let children = drop_surrounding_breaks(children);
+    if is_whitespace_only(&children) {
+        return vec![];
+    }
+    let children = match state.options.table_caption {
+        TableCaption::Emphasis => vec![mdast::Node::Emphasis(mdast::Emphasis { children })],
+        _ => children,
+    };
+    vec![mdast::Node::Paragraph(mdast::Paragraph { children })]
 }
 
 /// Inspect a <table> element to determine alignment and whether it has a header.
@@ -1601,7 +1950,11 @@ fn inspect_table_node(
 /// <td>, <th> → TableCell
 /// Port of hast-util-to-mdast/lib/handlers/table-cell.js
 fn handle_table_cell(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
-    let children = all(state, handle);
+    let children = if state.options.table_cell_breaks {
+        super::tables::cell_lines(cell_blocks(state, handle))
+    } else {
+        all(state, handle)
+    };
     let colspan = get_attr(handle, "colspan")
         .and_then(|s| s.parse::<u32>().ok())
         .filter(|&n| n > 1);
@@ -1616,6 +1969,25 @@ fn handle_table_cell(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
     })]
 }
 
+/// Children of a cell, with each block-level child as its own block so
+/// that `<div>a</div><div>b</div>` stays two lines.
+fn cell_blocks(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
+    let mut result = Vec::new();
+    for child in handle.children.borrow().iter() {
+        let nodes = one(state, child);
+        let block = match child.data {
+            NodeData::Element { ref name, .. } => is_block_element(name.local.as_ref()),
+            _ => false,
+        };
+        if block && !nodes.is_empty() && nodes.iter().all(mdast::Node::is_phrasing) {
+            result.push(mdast::Node::Paragraph(mdast::Paragraph { children: nodes }));
+        } else {
+            result.extend(nodes);
+        }
+    }
+    result
+}
+
 /// <tr> → TableRow
 /// Port of hast-util-to-mdast/lib/handlers/table-row.js
 fn handle_table_row(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
@@ -1637,6 +2009,80 @@ fn handle_textarea(state: &mut State, handle: &Handle) -> Vec<mdast::Node> {
     vec![mdast::Node::Text(mdast::Text { value: text })]
 }
 
+/// Elements that may be kept as raw HTML: phrasing markup Markdown has no
+/// syntax for, plus `details`. Links, media and ignored elements are not
+/// eligible, since their attributes would be lost or their content leaked.
+pub(crate) const KEEPABLE_HTML: &[&str] = &[
+    "abbr", "details", "del", "ins", "kbd", "mark", "s", "samp", "small", "sub", "sup", "time",
+    "u", "var",
+];
+
+/// Attributes kept on elements passed through as raw HTML.
+const KEPT_HTML_ATTRIBUTES: &[&str] = &["title", "open", "datetime"];
+
+/// Allowlisted element → its tags as raw HTML around the converted
+/// children: `InlineHtml` for phrasing content, `Html` blocks for block
+/// elements (`<details>`) or block content.
+fn handle_kept_html(state: &mut State, handle: &Handle, tag: &str) -> Vec<mdast::Node> {
+    let mut open = format!("<{tag}");
+    if let NodeData::Element { ref attrs, .. } = handle.data {
+        for attr in attrs.borrow().iter() {
+            let name = attr.name.local.as_ref();
+            if KEPT_HTML_ATTRIBUTES.contains(&name) {
+                open.push_str(&format!(
+                    " {name}=\"{}\"",
+                    super::util::escape(&attr.value, true)
+                ));
+            }
+        }
+    }
+    open.push('>');
+    let close = format!("</{tag}>");
+
+    if tag == "details" {
+        // GitHub renders Markdown inside <details> when blank lines
+        // separate it from the tags; the summary stays on the opening line.
+        let summary = handle
+            .children
+            .borrow()
+            .iter()
+            .find(|child| is_tag(child, "summary"))
+            .cloned();
+        if let Some(summary) = &summary {
+            let text = collapse_whitespace(&to_text(summary));
+            open.push_str(&format!(
+                "\n<summary>{}</summary>",
+                super::util::escape(text.trim(), false)
+            ));
+        }
+        let mut children = Vec::new();
+        for child in handle.children.borrow().iter() {
+            if !is_tag(child, "summary") {
+                children.extend(one(state, child));
+            }
+        }
+        let mut result = vec![mdast::Node::Html(mdast::Html { value: open })];
+        result.extend(super::wrap::wrap(children));
+        result.push(mdast::Node::Html(mdast::Html { value: close }));
+        return result;
+    }
+
+    let children = all(state, handle);
+    if is_whitespace_only(&children) {
+        return vec![];
+    }
+    if is_block_element(tag) || super::wrap::wrap_needed(&children) {
+        let mut result = vec![mdast::Node::Html(mdast::Html { value: open })];
+        result.extend(super::wrap::wrap(children));
+        result.push(mdast::Node::Html(mdast::Html { value: close }));
+        return result;
+    }
+    let mut result = vec![mdast::Node::InlineHtml(mdast::InlineHtml { value: open })];
+    result.extend(children);
+    result.push(mdast::Node::InlineHtml(mdast::InlineHtml { value: close }));
+    result
+}
+
 /// <wbr> → Text (zero-width space)
 /// Port of hast-util-to-mdast/lib/handlers/wbr.js
 fn handle_wbr() -> Vec<mdast::Node> {
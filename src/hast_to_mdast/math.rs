@@ -0,0 +1,495 @@
+// Math: MathML, KaTeX and MathJax to TeX.
+//
+// Renderers keep the TeX source around: MathML `<annotation
+// encoding="application/x-tex">` (also inside KaTeX's `.katex-mathml`),
+// MathJax 2's `<script type="math/tex">`, and MathJax 3's assistive MathML.
+// Where there is none, MathML presentation markup is converted to TeX on a
+// best-effort basis. Unrendered `\(...\)` in text is picked up as well.
+// MathJax 2's rendered output is dropped, as its script holds the source.
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::handlers::{get_attr, has_class, to_text};
+use super::{State, MAX_DEPTH};
+use crate::mdast::{self, Node};
+
+/// Classes of MathJax 2 output rendered next to its `math/tex` script.
+const MATHJAX_RENDERED: &[&str] = &[
+    "MathJax_Preview",
+    "MathJax",
+    "MathJax_Display",
+    "MathJax_SVG",
+    "MathJax_SVG_Display",
+    "MathJax_CHTML",
+    "MathJax_MathML",
+];
+
+/// Whether `handle` is math markup that [`handle`] converts.
+pub(crate) fn is_math(handle: &Handle, tag: &str) -> bool {
+    match tag {
+        "math" | "mjx-container" => true,
+        "script" => script_mode(handle).is_some(),
+        _ => {
+            has_class(handle, "katex")
+                || has_class(handle, "katex-display")
+                || MATHJAX_RENDERED
+                    .iter()
+                    .any(|class| has_class(handle, class))
+        }
+    }
+}
+
+/// Math markup → `Math` (display) or `InlineMath`. Display math in a table
+/// cell is inline, as cells hold a single line.
+pub(crate) fn handle(state: &State, handle: &Handle, tag: &str) -> Vec<Node> {
+    let (tex, display) = match tag {
+        "script" => {
+            let display = script_mode(handle) == Some(true);
+            (to_text(handle), display)
+        }
+        "math" => (math_tex(handle), is_display(handle)),
+        "mjx-container" => {
+            let display = get_attr(handle, "display").as_deref() == Some("true");
+            match find(handle, "math", 0) {
+                Some(math) => (math_tex(&math), display),
+                None => return vec![],
+            }
+        }
+        _ if has_class(handle, "katex") || has_class(handle, "katex-display") => {
+            let display = has_class(handle, "katex-display");
+            match find(handle, "math", 0) {
+                Some(math) => (math_tex(&math), display),
+                None => return vec![],
+            }
+        }
+        // MathJax 2 rendered output.
+        _ => return vec![],
+    };
+    let value = tex.trim().to_string();
+    if value.is_empty() {
+        return vec![];
+    }
+    if display && !state.in_table {
+        vec![Node::Math(mdast::Math { value })]
+    } else {
+        vec![Node::InlineMath(mdast::InlineMath { value })]
+    }
+}
+
+/// `Some(display)` for MathJax 2 `math/tex` scripts.
+fn script_mode(handle: &Handle) -> Option<bool> {
+    let kind = get_attr(handle, "type")?.to_ascii_lowercase();
+    let mut parts = kind.split(';').map(str::trim);
+    if parts.next() != Some("math/tex") {
+        return None;
+    }
+    Some(parts.any(|part| part == "mode=display"))
+}
+
+fn is_display(math: &Handle) -> bool {
+    get_attr(math, "display").as_deref() == Some("block")
+        || get_attr(math, "mode").as_deref() == Some("display")
+}
+
+/// TeX for a `<math>` element: its TeX annotation, or its presentation
+/// markup converted.
+fn math_tex(math: &Handle) -> String {
+    if let Some(annotation) = find_annotation(math, 0) {
+        return to_text(&annotation);
+    }
+    to_tex(math, 0)
+}
+
+fn find_annotation(handle: &Handle, depth: usize) -> Option<Handle> {
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+    for child in handle.children.borrow().iter() {
+        if let NodeData::Element { ref name, .. } = child.data {
+            if name.local.as_ref() == "annotation"
+                && get_attr(child, "encoding")
+                    .is_some_and(|encoding| encoding.eq_ignore_ascii_case("application/x-tex"))
+            {
+                return Some(child.clone());
+            }
+            if let Some(found) = find_annotation(child, depth + 1) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn find(handle: &Handle, tag: &str, depth: usize) -> Option<Handle> {
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+    for child in handle.children.borrow().iter() {
+        if let NodeData::Element { ref name, .. } = child.data {
+            if name.local.as_ref() == tag {
+                return Some(child.clone());
+            }
+            if let Some(found) = find(child, tag, depth + 1) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// `\(...\)` in text
+// ---------------------------------------------------------------------------
+
+/// Split text on `\(...\)` into `Text` and `InlineMath` nodes.
+pub(crate) fn split_inline(text: &str) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut pending = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("\\(") {
+        let Some(len) = rest[start + 2..].find("\\)") else {
+            break;
+        };
+        let tex = rest[start + 2..start + 2 + len].trim();
+        if tex.is_empty() {
+            pending.push_str(&rest[..start + 4 + len]);
+        } else {
+            pending.push_str(&rest[..start]);
+            if !pending.is_empty() {
+                nodes.push(text_node(&std::mem::take(&mut pending)));
+            }
+            nodes.push(Node::InlineMath(mdast::InlineMath {
+                value: tex.to_string(),
+            }));
+        }
+        rest = &rest[start + 4 + len..];
+    }
+    pending.push_str(rest);
+    if !pending.is_empty() {
+        nodes.push(text_node(&pending));
+    }
+    nodes
+}
+
+/// Display math among other phrasing becomes inline; a paragraph holding
+/// only display math is replaced by it.
+pub(crate) fn paragraph_math(children: Vec<Node>) -> Vec<Node> {
+    let others = children.iter().any(|child| match child {
+        Node::Math(_) => false,
+        Node::Text(text) => !text.value.trim().is_empty(),
+        _ => true,
+    });
+    if !others {
+        return children
+            .into_iter()
+            .filter(|child| matches!(child, Node::Math(_)))
+            .collect();
+    }
+    let children = children
+        .into_iter()
+        .map(|child| match child {
+            Node::Math(math) => Node::InlineMath(mdast::InlineMath { value: math.value }),
+            child => child,
+        })
+        .collect();
+    vec![Node::Paragraph(mdast::Paragraph { children })]
+}
+
+fn text_node(value: &str) -> Node {
+    Node::Text(mdast::Text {
+        value: value.to_string(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// MathML → TeX
+// ---------------------------------------------------------------------------
+
+/// Best-effort TeX for MathML presentation markup.
+fn to_tex(handle: &Handle, depth: usize) -> String {
+    if depth >= MAX_DEPTH {
+        return String::new();
+    }
+    let NodeData::Element { ref name, .. } = handle.data else {
+        return String::new();
+    };
+    let children: Vec<Handle> = handle
+        .children
+        .borrow()
+        .iter()
+        .filter(|child| matches!(child.data, NodeData::Element { .. }))
+        .cloned()
+        .collect();
+    let arg = |i: usize| {
+        children
+            .get(i)
+            .map(|child| to_tex(child, depth + 1))
+            .unwrap_or_default()
+    };
+    let row = || join(children.iter().map(|child| to_tex(child, depth + 1)));
+    match name.local.as_ref() {
+        "mi" => identifier(to_text(handle).trim()),
+        "mn" => to_text(handle).trim().to_string(),
+        "mo" => operator(to_text(handle).trim()),
+        "mtext" | "ms" => {
+            let text = to_text(handle);
+            let text = text.trim();
+            if text.is_empty() {
+                String::new()
+            } else {
+                format!("\\text{{{text}}}")
+            }
+        }
+        "mspace" => "\\ ".to_string(),
+        "msup" => format!("{}^{}", group(&arg(0)), group(&arg(1))),
+        "msub" => format!("{}_{}", group(&arg(0)), group(&arg(1))),
+        "msubsup" | "munderover" => {
+            format!("{}_{}^{}", group(&arg(0)), group(&arg(1)), group(&arg(2)))
+        }
+        "mfrac" => format!("\\frac{{{}}}{{{}}}", arg(0), arg(1)),
+        "msqrt" => format!("\\sqrt{{{}}}", row()),
+        "mroot" => format!("\\sqrt[{}]{{{}}}", arg(1), arg(0)),
+        "mover" => match accent(to_text(children.get(1).unwrap_or(handle)).trim()) {
+            Some(command) => format!("{command}{{{}}}", arg(0)),
+            None => format!("\\overset{{{}}}{{{}}}", arg(1), arg(0)),
+        },
+        "munder" => format!("\\underset{{{}}}{{{}}}", arg(1), arg(0)),
+        "mfenced" => {
+            let open = get_attr(handle, "open").unwrap_or_else(|| "(".to_string());
+            let close = get_attr(handle, "close").unwrap_or_else(|| ")".to_string());
+            let separator = get_attr(handle, "separators").unwrap_or_else(|| ",".to_string());
+            let separator = separator.trim().chars().next().map(String::from);
+            let items: Vec<String> = children
+                .iter()
+                .map(|child| to_tex(child, depth + 1))
+                .collect();
+            format!(
+                "\\left{}{}\\right{}",
+                fence(&open),
+                items.join(separator.as_deref().unwrap_or("")),
+                fence(&close)
+            )
+        }
+        "mtable" => {
+            let rows: Vec<String> = children
+                .iter()
+                .map(|row| {
+                    let cells: Vec<String> = row
+                        .children
+                        .borrow()
+                        .iter()
+                        .filter(|cell| matches!(cell.data, NodeData::Element { .. }))
+                        .map(|cell| to_tex(cell, depth + 2))
+                        .collect();
+                    cells.join(" & ")
+                })
+                .collect();
+            format!("\\begin{{matrix}}{}\\end{{matrix}}", rows.join(" \\\\ "))
+        }
+        // The first child of `semantics` is the presentation markup.
+        "semantics" => arg(0),
+        "annotation" | "annotation-xml" | "mphantom" | "none" | "mprescripts" => String::new(),
+        // math, mrow, mstyle, mpadded, menclose, merror, mtd, …
+        _ => row(),
+    }
+}
+
+/// Concatenate TeX fragments, separating a command from a following letter.
+fn join(parts: impl Iterator<Item = String>) -> String {
+    let mut tex = String::new();
+    for part in parts.filter(|part| !part.is_empty()) {
+        let ends_with_command = tex
+            .rsplit_once('\\')
+            .is_some_and(|(_, tail)| !tail.is_empty() && tail.chars().all(char::is_alphabetic));
+        if ends_with_command && part.starts_with(char::is_alphabetic) {
+            tex.push(' ');
+        }
+        tex.push_str(&part);
+    }
+    tex
+}
+
+/// Wrap `tex` in braces unless it is a single character or command.
+fn group(tex: &str) -> String {
+    let single = tex.chars().count() == 1
+        || tex
+            .strip_prefix('\\')
+            .is_some_and(|command| !command.is_empty() && command.chars().all(char::is_alphabetic));
+    if single {
+        tex.to_string()
+    } else {
+        format!("{{{tex}}}")
+    }
+}
+
+/// Known function names, written as commands.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "max", "min", "sup", "inf", "det", "dim", "gcd", "deg", "arg",
+    "ker", "Pr",
+];
+
+fn identifier(text: &str) -> String {
+    if let Some(symbol) = symbol(text) {
+        return symbol.to_string();
+    }
+    if FUNCTIONS.contains(&text) {
+        return format!("\\{text}");
+    }
+    if text.chars().count() > 1 {
+        return format!("\\mathrm{{{text}}}");
+    }
+    text.to_string()
+}
+
+fn operator(text: &str) -> String {
+    if let Some(symbol) = symbol(text) {
+        return symbol.to_string();
+    }
+    match text {
+        "\u{2061}" | "\u{2062}" | "\u{2063}" => String::new(),
+        "{" | "}" | "$" | "%" | "&" | "#" => format!("\\{text}"),
+        _ if FUNCTIONS.contains(&text) => format!("\\{text}"),
+        _ => text.to_string(),
+    }
+}
+
+fn fence(delimiter: &str) -> String {
+    match delimiter {
+        "" => ".".to_string(),
+        "{" | "}" => format!("\\{delimiter}"),
+        other => operator(other),
+    }
+}
+
+fn accent(text: &str) -> Option<&'static str> {
+    Some(match text {
+        "^" | "\u{302}" | "\u{2c6}" => "\\hat",
+        "\u{af}" | "\u{304}" | "\u{203e}" | "_" => "\\overline",
+        "\u{2192}" | "\u{20d7}" => "\\vec",
+        "~" | "\u{2dc}" | "\u{303}" => "\\tilde",
+        "\u{2d9}" | "\u{307}" | "." => "\\dot",
+        "\u{a8}" | "\u{308}" => "\\ddot",
+        _ => return None,
+    })
+}
+
+/// TeX commands for Greek letters and common symbols.
+fn symbol(text: &str) -> Option<&'static str> {
+    Some(match text {
+        "α" => "\\alpha",
+        "β" => "\\beta",
+        "γ" => "\\gamma",
+        "δ" => "\\delta",
+        "ε" | "ϵ" => "\\epsilon",
+        "ζ" => "\\zeta",
+        "η" => "\\eta",
+        "θ" => "\\theta",
+        "ι" => "\\iota",
+        "κ" => "\\kappa",
+        "λ" => "\\lambda",
+        "μ" => "\\mu",
+        "ν" => "\\nu",
+        "ξ" => "\\xi",
+        "π" => "\\pi",
+        "ρ" => "\\rho",
+        "σ" => "\\sigma",
+        "τ" => "\\tau",
+        "υ" => "\\upsilon",
+        "φ" | "ϕ" => "\\phi",
+        "χ" => "\\chi",
+        "ψ" => "\\psi",
+        "ω" => "\\omega",
+        "Γ" => "\\Gamma",
+        "Δ" => "\\Delta",
+        "Θ" => "\\Theta",
+        "Λ" => "\\Lambda",
+        "Ξ" => "\\Xi",
+        "Π" => "\\Pi",
+        "Σ" => "\\Sigma",
+        "Υ" => "\\Upsilon",
+        "Φ" => "\\Phi",
+        "Ψ" => "\\Psi",
+        "Ω" => "\\Omega",
+        "∞" => "\\infty",
+        "∂" => "\\partial",
+        "∇" => "\\nabla",
+        "×" => "\\times",
+        "÷" => "\\div",
+        "·" | "⋅" => "\\cdot",
+        "±" => "\\pm",
+        "∓" => "\\mp",
+        "−" => "-",
+        "≤" => "\\leq",
+        "≥" => "\\geq",
+        "≠" => "\\neq",
+        "≈" => "\\approx",
+        "≡" => "\\equiv",
+        "∼" => "\\sim",
+        "∝" => "\\propto",
+        "→" => "\\to",
+        "←" => "\\leftarrow",
+        "⇒" => "\\Rightarrow",
+        "⇔" => "\\Leftrightarrow",
+        "↦" => "\\mapsto",
+        "∈" => "\\in",
+        "∉" => "\\notin",
+        "⊂" => "\\subset",
+        "⊆" => "\\subseteq",
+        "∪" => "\\cup",
+        "∩" => "\\cap",
+        "∅" => "\\emptyset",
+        "∀" => "\\forall",
+        "∃" => "\\exists",
+        "¬" => "\\neg",
+        "∧" => "\\wedge",
+        "∨" => "\\vee",
+        "∑" => "\\sum",
+        "∏" => "\\prod",
+        "∫" => "\\int",
+        "∮" => "\\oint",
+        "√" => "\\surd",
+        "…" => "\\ldots",
+        "⋯" => "\\cdots",
+        "′" => "'",
+        "ℝ" => "\\mathbb{R}",
+        "ℕ" => "\\mathbb{N}",
+        "ℤ" => "\\mathbb{Z}",
+        "ℚ" => "\\mathbb{Q}",
+        "ℂ" => "\\mathbb{C}",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_inline_finds_delimiters() {
+        let nodes = split_inline("a \\(x^2\\) b \\( \\) c \\(open");
+        assert_eq!(
+            nodes,
+            vec![
+                text_node("a "),
+                Node::InlineMath(mdast::InlineMath {
+                    value: "x^2".to_string()
+                }),
+                text_node(" b \\( \\) c \\(open"),
+            ]
+        );
+    }
+
+    #[test]
+    fn join_separates_commands_from_letters() {
+        let parts = ["\\alpha", "x", "\\cdot", "2"].map(String::from);
+        assert_eq!(join(parts.into_iter()), "\\alpha x\\cdot2");
+    }
+
+    #[test]
+    fn group_braces_multi_character() {
+        assert_eq!(group("x"), "x");
+        assert_eq!(group("\\pi"), "\\pi");
+        assert_eq!(group("ab"), "{ab}");
+    }
+}
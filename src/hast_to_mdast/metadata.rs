@@ -0,0 +1,338 @@
+// Document metadata extraction.
+//
+// Collects the page-level fields a static-site pipeline usually needs
+// (`<title>`, `<meta>` description/author/date, canonical link, `lang`, Open
+// Graph and Twitter card tags) from the parsed tree, and renders them as YAML
+// frontmatter for an optional leading `Yaml` node.
+
+use std::collections::BTreeMap;
+
+use markup5ever_rcdom::{Handle, NodeData};
+use serde_json::Value;
+
+use super::handlers::{get_attr, to_text};
+use super::structured::StructuredData;
+use super::util::normalize_whitespace;
+use super::{State, MAX_DEPTH};
+
+/// Page metadata gathered from `<head>` (and `<html lang>`).
+///
+/// Core fields fall back to their Open Graph or Twitter equivalents when the
+/// plain HTML source is missing, e.g. `title` comes from `<title>`, then
+/// `og:title`, then `twitter:title`. URLs are resolved against `<base>`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// `<title>`, `og:title`, or `twitter:title`.
+    pub title: Option<String>,
+    /// `meta[name=description]`, `og:description`, or `twitter:description`.
+    pub description: Option<String>,
+    /// `meta[name=author]`, `article:author`, or `twitter:creator`.
+    pub author: Option<String>,
+    /// `article:published_time`, `meta[name=date]`, or `itemprop=datePublished`.
+    pub published: Option<String>,
+    /// `article:modified_time` or `itemprop=dateModified`.
+    pub modified: Option<String>,
+    /// `link[rel=canonical]` or `og:url`.
+    pub canonical: Option<String>,
+    /// `<html lang>` or `meta[http-equiv=content-language]`.
+    pub lang: Option<String>,
+    /// `og:site_name`.
+    pub site_name: Option<String>,
+    /// `og:image` or `twitter:image`.
+    pub image: Option<String>,
+    /// `meta[name=keywords]`, split on commas.
+    pub keywords: Vec<String>,
+    /// All `og:*` properties, keyed without the prefix (first value wins).
+    pub open_graph: BTreeMap<String, String>,
+    /// All `twitter:*` tags, keyed without the prefix (first value wins).
+    pub twitter: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    /// Whether no metadata was found.
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+
+    /// Render the core fields as YAML frontmatter (without `---` fences).
+    ///
+    /// Returns `None` when there is nothing to write.
+    pub fn to_yaml(&self) -> Option<String> {
+        render_yaml(&self.yaml_fields())
+    }
+
+    /// The frontmatter fields as `(key, rendered value)` pairs, in output order.
+    fn yaml_fields(&self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        let scalars = [
+            ("title", &self.title),
+            ("description", &self.description),
+            ("author", &self.author),
+            ("date", &self.published),
+            ("updated", &self.modified),
+            ("canonical", &self.canonical),
+            ("lang", &self.lang),
+            ("site_name", &self.site_name),
+            ("image", &self.image),
+        ];
+        for (key, value) in scalars {
+            if let Some(value) = value {
+                fields.push((key.to_string(), yaml_scalar(value)));
+            }
+        }
+        if !self.keywords.is_empty() {
+            let items: Vec<String> = self.keywords.iter().map(|k| yaml_scalar(k)).collect();
+            fields.push(("keywords".to_string(), format!("[{}]", items.join(", "))));
+        }
+        fields
+    }
+}
+
+/// Build the frontmatter for `metadata`, overriding or extending it with
+/// structured-data properties: each `(key, path)` pair looks `path` up with
+/// [`StructuredData::find`] and, when found, writes it under `key`.
+pub(crate) fn frontmatter(
+    metadata: &Metadata,
+    data: &StructuredData,
+    properties: &[(String, String)],
+) -> Option<String> {
+    let mut fields = metadata.yaml_fields();
+    for (key, path) in properties {
+        let Some(value) = data.find(path).as_ref().and_then(yaml_value) else {
+            continue;
+        };
+        match fields.iter_mut().find(|(k, _)| k == key) {
+            Some(field) => field.1 = value,
+            None => fields.push((key.clone(), value)),
+        }
+    }
+    render_yaml(&fields)
+}
+
+fn render_yaml(fields: &[(String, String)]) -> Option<String> {
+    if fields.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}: {value}", yaml_scalar(key)))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// Render a JSON value as an inline YAML value. Objects collapse to their
+/// `name` (the usual shape of schema.org `Person`/`Organization`) or `@id`.
+fn yaml_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(yaml_scalar(s.trim())),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter_map(yaml_value).collect();
+            Some(format!("[{}]", items.join(", ")))
+        }
+        Value::Object(object) => ["name", "@value", "@id", "url"]
+            .iter()
+            .find_map(|key| object.get(*key))
+            .and_then(yaml_value),
+    }
+}
+
+/// Extract metadata from the document. Run after the transform so `<base>`
+/// has been seen and URLs resolve the same way links in the body do.
+pub(crate) fn extract(state: &State, document: &Handle) -> Metadata {
+    let mut raw = Raw::default();
+    collect(document, &mut raw, 0);
+
+    let og = |key: &str| raw.open_graph.get(key).cloned();
+    let tw = |key: &str| raw.twitter.get(key).cloned();
+    let named = |key: &str| raw.named.get(key).cloned();
+    let resolve = |url: String| state.resolve(&url);
+
+    Metadata {
+        title: raw
+            .title
+            .clone()
+            .or_else(|| og("title"))
+            .or_else(|| tw("title")),
+        description: named("description")
+            .or_else(|| og("description"))
+            .or_else(|| tw("description")),
+        author: named("author")
+            .or_else(|| named("article:author"))
+            .or_else(|| tw("creator")),
+        published: named("article:published_time")
+            .or_else(|| named("date"))
+            .or_else(|| named("pubdate"))
+            .or_else(|| named("dc.date.issued"))
+            .or_else(|| named("dc.date"))
+            .or_else(|| named("datepublished")),
+        modified: named("article:modified_time").or_else(|| named("datemodified")),
+        canonical: raw.canonical.clone().or_else(|| og("url")).map(resolve),
+        lang: raw.lang.clone().or_else(|| named("content-language")),
+        site_name: og("site_name"),
+        image: og("image").or_else(|| tw("image")).map(resolve),
+        keywords: named("keywords")
+            .map(|k| {
+                k.split(',')
+                    .map(|k| k.trim().to_string())
+                    .filter(|k| !k.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        open_graph: raw.open_graph,
+        twitter: raw.twitter,
+    }
+}
+
+/// Values as found, before fallbacks are applied.
+#[derive(Default)]
+struct Raw {
+    title: Option<String>,
+    canonical: Option<String>,
+    lang: Option<String>,
+    /// `<meta>` keyed by lowercased `name`, `property`, `itemprop`, or
+    /// `http-equiv` (other than `og:`/`twitter:`).
+    named: BTreeMap<String, String>,
+    open_graph: BTreeMap<String, String>,
+    twitter: BTreeMap<String, String>,
+}
+
+fn collect(handle: &Handle, raw: &mut Raw, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if let NodeData::Element { ref name, .. } = handle.data {
+        match name.local.as_ref() {
+            "html" if raw.lang.is_none() => {
+                raw.lang = non_empty(get_attr(handle, "lang"));
+            }
+            "title" => {
+                if raw.title.is_none() {
+                    raw.title = non_empty(Some(to_text(handle)));
+                }
+                return;
+            }
+            "link" => {
+                let rel = get_attr(handle, "rel").unwrap_or_default();
+                if raw.canonical.is_none()
+                    && rel
+                        .split_ascii_whitespace()
+                        .any(|r| r.eq_ignore_ascii_case("canonical"))
+                {
+                    raw.canonical = non_empty(get_attr(handle, "href"));
+                }
+            }
+            "meta" => collect_meta(handle, raw),
+            // `<title>` inside inline SVG is not the document title.
+            "svg" | "math" | "template" | "script" | "style" => return,
+            _ => {}
+        }
+    }
+    for child in handle.children.borrow().iter() {
+        collect(child, raw, depth + 1);
+    }
+}
+
+fn collect_meta(handle: &Handle, raw: &mut Raw) {
+    let Some(content) = non_empty(get_attr(handle, "content")) else {
+        return;
+    };
+    for attr in ["property", "name", "itemprop", "http-equiv"] {
+        let Some(key) = get_attr(handle, attr) else {
+            continue;
+        };
+        // RDFa allows several space-separated properties on one tag.
+        for key in key.split_ascii_whitespace() {
+            let key = key.to_ascii_lowercase();
+            let (map, key) = if let Some(rest) = key.strip_prefix("og:") {
+                (&mut raw.open_graph, rest.to_string())
+            } else if let Some(rest) = key.strip_prefix("twitter:") {
+                (&mut raw.twitter, rest.to_string())
+            } else {
+                (&mut raw.named, key)
+            };
+            map.entry(key).or_insert_with(|| content.clone());
+        }
+    }
+}
+
+/// Trim and collapse whitespace; `None` if nothing is left.
+fn non_empty(value: Option<String>) -> Option<String> {
+    let value = normalize_whitespace(&value?);
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Format a string as a YAML scalar, quoting only when a plain scalar would
+/// be misread (special leading characters, `: ` or ` #`, reserved words,
+/// numbers).
+pub(crate) fn yaml_scalar(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value.chars().next().is_some_and(char::is_alphanumeric)
+        && !value.ends_with(' ')
+        && !value.ends_with(':')
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value
+            .chars()
+            .any(|c| c.is_control() || matches!(c, '"' | '\\' | '[' | ']' | '{' | '}' | ','))
+        && !matches!(
+            value.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "y" | "n"
+        )
+        && value.parse::<f64>().is_err();
+    if plain {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_scalar_quotes_only_when_needed() {
+        assert_eq!(yaml_scalar("Hello world"), "Hello world");
+        assert_eq!(yaml_scalar("2024-01-05"), "2024-01-05");
+        assert_eq!(yaml_scalar("Rust: the book"), "\"Rust: the book\"");
+        assert_eq!(yaml_scalar("#hashtag"), "\"#hashtag\"");
+        assert_eq!(yaml_scalar("yes"), "\"yes\"");
+        assert_eq!(yaml_scalar("1.5"), "\"1.5\"");
+        assert_eq!(yaml_scalar("a, b"), "\"a, b\"");
+        assert_eq!(yaml_scalar("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+    }
+
+    #[test]
+    fn to_yaml_orders_fields_and_skips_missing() {
+        let metadata = Metadata {
+            title: Some("T".into()),
+            lang: Some("en".into()),
+            keywords: vec!["a".into(), "b c".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.to_yaml().unwrap(),
+            "title: T\nlang: en\nkeywords: [a, b c]"
+        );
+        assert_eq!(Metadata::default().to_yaml(), None);
+    }
+}
@@ -4,7 +4,21 @@
 // Parses HTML via html5ever and walks the resulting tree, dispatching each
 // element to a handler that produces MDAST nodes.
 
+pub(crate) mod admonitions;
+pub(crate) mod code;
+pub(crate) mod custom;
+pub(crate) mod footnotes;
+pub(crate) mod fragments;
+pub(crate) mod guess;
 pub(crate) mod handlers;
+pub(crate) mod math;
+pub(crate) mod metadata;
+pub(crate) mod readability;
+pub(crate) mod references;
+pub(crate) mod select;
+pub(crate) mod structured;
+pub(crate) mod table_data;
+pub(crate) mod tables;
 pub(crate) mod util;
 pub(crate) mod whitespace;
 pub(crate) mod wrap;
@@ -19,6 +33,8 @@ use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use url::Url;
 
 use crate::mdast;
+use custom::ElementHandlers;
+use select::Selector;
 
 /// Options for the HTML → MDAST transformation.
 #[derive(Debug, Clone, Default)]
@@ -33,6 +49,84 @@ pub struct TransformOptions {
     /// Each string is 1 or 2 characters: open (and close if different).
     /// Default: `['"']` (ASCII double-quote).
     pub quotes: Vec<String>,
+    /// Convert only the outermost elements matching this selector.
+    pub root_selector: Option<Selector>,
+    /// Skip elements matching any of these selectors (and their contents).
+    pub exclude_selectors: Vec<Selector>,
+    /// Convert only the main content, as scored by `readability`.
+    pub main_content: bool,
+    /// Emit a leading `Yaml` node with the document's metadata.
+    pub frontmatter: bool,
+    /// `(key, property path)` pairs merged into the frontmatter from
+    /// structured data.
+    pub frontmatter_properties: Vec<(String, String)>,
+    /// Whether links and images are written inline or as references.
+    pub link_style: references::LinkStyle,
+    /// Where reference definitions go.
+    pub definition_placement: references::DefinitionPlacement,
+    /// Which element attributes are kept as `{#id .class}` blocks.
+    pub keep_attributes: KeepAttributes,
+    /// Point `#id` links at the generated slugs of their target headings.
+    pub rewrite_fragment_links: bool,
+    /// Code language aliases, keyed by lowercased alias.
+    pub language_aliases: HashMap<String, String>,
+    /// Guess the language of unlabelled code blocks, keeping guesses at or
+    /// above this confidence.
+    pub language_guess_threshold: Option<f32>,
+    /// Remove indentation shared by every line of a code block.
+    pub code_dedent: bool,
+    /// Whether line-number gutters and copy buttons are left out of code.
+    pub code_strip_chrome: bool,
+    /// Move leading `$ `/`>>> ` prompts out of code blocks into `meta`.
+    pub code_strip_prompts: bool,
+    /// Fill `Code.meta` from attributes and captions.
+    pub code_meta: bool,
+    /// `(attribute, template)` pairs for code meta; `{}` is the value.
+    pub code_meta_attributes: Vec<(String, String)>,
+    /// Whether lossy tables are kept as HTML.
+    pub table_strategy: tables::TableStrategy,
+    /// Keep line structure in table cells with `<br>`.
+    pub table_cell_breaks: bool,
+    /// Repeat spanning cells' content in the slots they cover.
+    pub table_duplicate_spans: bool,
+    /// Whether table captions are written, and how.
+    pub table_caption: tables::TableCaption,
+    /// Promote a bold first row of a headless table to the header.
+    pub table_promote_header: bool,
+    /// Unwrap tables used for page layout into flow content.
+    pub unwrap_layout_tables: bool,
+    /// Tag names passed through as raw HTML.
+    pub keep_html: Vec<String>,
+    /// Create `Highlight` nodes for `<mark>`.
+    pub highlight: bool,
+    /// Create `Superscript` nodes for `<sup>`.
+    pub superscript: bool,
+    /// Create `Subscript` nodes for `<sub>`.
+    pub subscript: bool,
+    /// Create `Insert` nodes for `<ins>` and `<u>`.
+    pub insert: bool,
+    /// Create `Math`/`InlineMath` nodes from MathML, KaTeX, MathJax and
+    /// `\(...\)`.
+    pub math: bool,
+    /// Create `DefinitionList` nodes for `<dl>`.
+    pub definition_lists: bool,
+    /// Create `Admonition` nodes for notes, tips and warnings.
+    pub admonitions: bool,
+    /// `(class, kind)` pairs recognizing admonitions, checked in order.
+    pub admonition_kinds: Vec<(String, String)>,
+}
+
+/// Which HTML attributes are kept as `{#id .class}` attribute blocks on
+/// headings (the syntax of Pandoc, kramdown and markdown-it-attrs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeepAttributes {
+    /// Drop attributes (default).
+    #[default]
+    None,
+    /// Keep `id` as `{#id}`.
+    Id,
+    /// Keep `id` and `class` as `{#id .class}`.
+    IdAndClasses,
 }
 
 /// Maximum recursion depth for tree traversal. Prevents stack overflow on
@@ -54,14 +148,24 @@ pub(crate) struct State {
     pub q_nesting: usize,
     /// Elements indexed by their `id` attribute.
     pub element_by_id: HashMap<String, Handle>,
+    /// Footnote references found in the pre-pass and numbered on use.
+    pub footnotes: footnotes::Footnotes,
+    /// Id → heading map and heading slugs for fragment link rewriting.
+    pub fragments: fragments::Fragments,
     /// Transform options.
     pub options: TransformOptions,
+    /// Custom element handlers, consulted before the built-in dispatch.
+    pub handlers: ElementHandlers,
     /// Current recursion depth for tree traversal.
     pub depth: usize,
+    /// Outermost tables seen so far, when collecting them as data.
+    pub tables: Option<Vec<table_data::TableData>>,
+    /// Lossiness of the outermost tables seen so far, when collecting them.
+    pub table_losses: Option<Vec<Vec<tables::TableLoss>>>,
 }
 
 impl State {
-    fn new(options: TransformOptions) -> Self {
+    fn new(options: TransformOptions, handlers: ElementHandlers) -> Self {
         Self {
             frozen_base_url: None,
             base_found: false,
@@ -69,8 +173,13 @@ impl State {
             in_pre: false,
             q_nesting: 0,
             element_by_id: HashMap::new(),
+            footnotes: footnotes::Footnotes::default(),
+            fragments: fragments::Fragments::default(),
             options,
+            handlers,
             depth: 0,
+            tables: None,
+            table_losses: None,
         }
     }
 
@@ -88,21 +197,123 @@ impl State {
     }
 }
 
-/// Parse an HTML string and transform it into an MDAST tree.
-pub(crate) fn transform(html: &str, options: TransformOptions) -> mdast::Node {
+/// Parse an HTML string and transform it into an MDAST tree, collecting
+/// page data from the same parse. Metadata and structured data are only
+/// extracted for a `document`, or when the frontmatter needs them.
+pub(crate) fn transform(
+    html: &str,
+    options: TransformOptions,
+    handlers: &ElementHandlers,
+    document: bool,
+) -> crate::Document {
     let dom = parse_html(html);
-    let mut state = State::new(options);
+    let mut state = State::new(options, handlers.clone());
 
     // Pre-pass: index elements by id.
     index_ids(&dom.document, &mut state.element_by_id);
+    footnotes::index(&mut state, &dom.document);
+    if state.options.rewrite_fragment_links {
+        fragments::index(&mut state, &dom.document);
+    }
 
     // Transform.
-    let children = handlers::all(&mut state, &dom.document);
-    let children = wrap::wrap(children);
-    let mut root = mdast::Node::Root(mdast::Root { children });
-    whitespace::post_process_whitespace(&mut root);
+    let mut children = walk(&mut state, &dom.document);
+    children.extend(footnotes::definitions(&mut state));
+    if state.options.rewrite_fragment_links {
+        fragments::rewrite(&state, &mut children);
+    }
+
+    let metadata = if document || state.options.frontmatter {
+        metadata::extract(&state, &dom.document)
+    } else {
+        metadata::Metadata::default()
+    };
+    let properties = state.options.frontmatter && !state.options.frontmatter_properties.is_empty();
+    let structured_data = if document || properties {
+        structured::extract(&state, &dom.document)
+    } else {
+        structured::StructuredData::default()
+    };
+    if state.options.frontmatter {
+        let properties = &state.options.frontmatter_properties;
+        if let Some(value) = metadata::frontmatter(&metadata, &structured_data, properties) {
+            children.insert(0, mdast::Node::Yaml(mdast::Yaml { value }));
+        }
+    }
+
+    let mut tree = mdast::Node::Root(mdast::Root { children });
+    whitespace::post_process_whitespace(&mut tree);
+    if state.options.link_style == references::LinkStyle::Reference {
+        if let mdast::Node::Root(ref mut root) = tree {
+            references::to_references(&mut root.children, state.options.definition_placement);
+        }
+    }
+
+    crate::Document {
+        tree,
+        metadata,
+        structured_data,
+    }
+}
+
+/// Transform the document, or the subtrees picked by `root_selector` /
+/// `main_content`.
+fn walk(state: &mut State, document: &Handle) -> Vec<mdast::Node> {
+    let roots = match (&state.options.root_selector, state.options.main_content) {
+        (Some(selector), _) => Some(select::select_outermost(document, selector)),
+        (None, true) => Some(readability::main_content(document)),
+        (None, false) => None,
+    };
+    match roots {
+        Some(roots) => {
+            // Only the selected subtrees are walked, so pick up the document's
+            // `<base>` up front rather than relying on document order.
+            if let Some(base) = first_element(document, "base", 0) {
+                handlers::one(state, &base);
+            }
+            roots
+                .iter()
+                .flat_map(|root| wrap::wrap(handlers::one(state, root)))
+                .collect()
+        }
+        None => wrap::wrap(handlers::all(state, document)),
+    }
+}
+
+/// Every outermost table in the converted part of `html` as data, in
+/// document order.
+pub(crate) fn extract_tables(
+    html: &str,
+    options: TransformOptions,
+    handlers: &ElementHandlers,
+) -> Vec<table_data::TableData> {
+    collect_tables(html, options, handlers)
+        .tables
+        .unwrap_or_default()
+}
+
+/// Lossiness of each outermost table in the converted part of `html`, in
+/// document order.
+pub(crate) fn table_losses(
+    html: &str,
+    options: TransformOptions,
+    handlers: &ElementHandlers,
+) -> Vec<Vec<tables::TableLoss>> {
+    collect_tables(html, options, handlers)
+        .table_losses
+        .unwrap_or_default()
+}
 
-    root
+/// Walk the converted part of `html`, collecting its outermost tables.
+fn collect_tables(html: &str, options: TransformOptions, handlers: &ElementHandlers) -> State {
+    let dom = parse_html(html);
+    let mut state = State::new(options, handlers.clone());
+    index_ids(&dom.document, &mut state.element_by_id);
+    footnotes::index(&mut state, &dom.document);
+    state.tables = Some(Vec::new());
+    state.table_losses = Some(Vec::new());
+    walk(&mut state, &dom.document);
+    state
 }
 
 /// Parse an HTML string into an html5ever RcDom.
@@ -119,6 +330,21 @@ pub(crate) fn parse_html(html: &str) -> RcDom {
         .one(html.as_bytes())
 }
 
+/// Find the first element named `tag` in document order.
+fn first_element(handle: &Handle, tag: &str, depth: usize) -> Option<Handle> {
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+    if handlers::is_tag(handle, tag) {
+        return Some(handle.clone());
+    }
+    handle
+        .children
+        .borrow()
+        .iter()
+        .find_map(|child| first_element(child, tag, depth + 1))
+}
+
 /// Recursively index all elements by their `id` attribute.
 fn index_ids(handle: &Handle, map: &mut HashMap<String, Handle>) {
     index_ids_inner(handle, map, 0);
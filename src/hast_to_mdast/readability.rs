@@ -0,0 +1,337 @@
+// Main-content extraction.
+//
+// A compact take on Mozilla Readability's candidate scoring
+// (https://github.com/mozilla/readability), run on the tree `transform`
+// already parsed. Paragraph-like elements award points to their parent and
+// grandparent based on text length and comma count; candidates start from a
+// tag- and class/id-based weight and are finally discounted by link density.
+// The best candidate, plus siblings that score close to it, is converted.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::LazyLock;
+
+use markup5ever_rcdom::{Handle, NodeData};
+use regex::Regex;
+
+use super::handlers::{get_attr, is_tag};
+use super::select::parent_element;
+use super::MAX_DEPTH;
+
+/// Class/id fragments of boilerplate that is never the main content.
+static UNLIKELY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)-ad-|ai2html|banner|breadcrumbs|combx|comment|community|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|related|remark|replies|rss|shoutbox|sidebar|skyscraper|social|sponsor|supplemental|ad-break|agegate|pagination|pager|popup|yom-remote",
+    )
+    .unwrap()
+});
+
+/// Class/id fragments that rescue an otherwise unlikely element.
+static MAYBE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)and|article|body|column|content|main|shadow").unwrap());
+
+static POSITIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)article|body|content|entry|hentry|h-entry|main|page|pagination|post|text|blog|story",
+    )
+    .unwrap()
+});
+
+static NEGATIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)-ad-|hidden|^hid$| hid$| hid |^hid |banner|combx|comment|com-|contact|footer|gdpr|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|widget").unwrap()
+});
+
+/// Minimum text length for an element to count as a paragraph.
+const MIN_PARAGRAPH_LEN: usize = 25;
+
+/// Minimum text length of the winning candidate; below this the page is
+/// treated as having no identifiable main content.
+const MIN_CONTENT_LEN: usize = 140;
+
+struct Candidate {
+    handle: Handle,
+    score: f64,
+}
+
+/// Pick the subtrees holding the document's main content, in document order.
+///
+/// Returns the best-scoring candidate together with qualifying siblings, or
+/// `<body>` when nothing scores well enough.
+pub(crate) fn main_content(document: &Handle) -> Vec<Handle> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut index: HashMap<*const markup5ever_rcdom::Node, usize> = HashMap::new();
+    score_paragraphs(document, &mut candidates, &mut index, 0);
+
+    let best = candidates
+        .iter()
+        .map(|c| (c, c.score * (1.0 - link_density(&c.handle))))
+        .filter(|(c, _)| text_len(&c.handle) >= MIN_CONTENT_LEN)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+    let Some((best, best_score)) = best else {
+        return vec![body(document).unwrap_or_else(|| document.clone())];
+    };
+
+    // Siblings sharing the candidate's parent often carry the title or
+    // trailing paragraphs of the same article.
+    let Some(parent) = parent_element(&best.handle) else {
+        return vec![best.handle.clone()];
+    };
+    let threshold = f64::max(10.0, best_score * 0.2);
+    let class = get_attr(&best.handle, "class").filter(|c| !c.is_empty());
+    let children = parent.children.borrow();
+    children
+        .iter()
+        .filter(|sibling| {
+            if Rc::ptr_eq(sibling, &best.handle) {
+                return true;
+            }
+            if !matches!(sibling.data, NodeData::Element { .. }) {
+                return false;
+            }
+            let mut bonus = 0.0;
+            if class.is_some() && get_attr(sibling, "class") == class {
+                bonus = best_score * 0.2;
+            }
+            if let Some(&i) = index.get(&Rc::as_ptr(sibling)) {
+                let score = candidates[i].score * (1.0 - link_density(sibling));
+                if score + bonus >= threshold {
+                    return true;
+                }
+            }
+            // Headings beside the content are usually its title.
+            if ["h1", "h2", "h3"].iter().any(|h| is_tag(sibling, h)) {
+                return true;
+            }
+            if is_tag(sibling, "p") {
+                let len = text_len(sibling);
+                let density = link_density(sibling);
+                return (len > 80 && density < 0.25)
+                    || (len > 0 && density == 0.0 && has_sentence_end(sibling));
+            }
+            false
+        })
+        .cloned()
+        .collect()
+}
+
+/// Walk the tree, awarding paragraph scores to ancestors.
+fn score_paragraphs(
+    handle: &Handle,
+    candidates: &mut Vec<Candidate>,
+    index: &mut HashMap<*const markup5ever_rcdom::Node, usize>,
+    depth: usize,
+) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if let NodeData::Element { ref name, .. } = handle.data {
+        let tag = name.local.as_ref();
+        if matches!(
+            tag,
+            "script" | "style" | "noscript" | "template" | "nav" | "aside" | "footer" | "form"
+        ) || is_unlikely(handle)
+        {
+            return;
+        }
+        if matches!(tag, "p" | "pre" | "td" | "blockquote") || is_leaf_div(handle) {
+            score_paragraph(handle, candidates, index);
+            // Nested paragraphs (a <p> inside a <td>) are scored on their own.
+            if tag == "p" || tag == "pre" {
+                return;
+            }
+        }
+    }
+    for child in handle.children.borrow().iter() {
+        score_paragraphs(child, candidates, index, depth + 1);
+    }
+}
+
+fn score_paragraph(
+    handle: &Handle,
+    candidates: &mut Vec<Candidate>,
+    index: &mut HashMap<*const markup5ever_rcdom::Node, usize>,
+) {
+    let text = inner_text(handle);
+    let len = text.trim().chars().count();
+    if len < MIN_PARAGRAPH_LEN {
+        return;
+    }
+    let commas = text.matches([',', '，', '、']).count();
+    let score = 1.0 + commas as f64 + f64::min((len / 100) as f64, 3.0);
+
+    let Some(parent) = parent_element(handle) else {
+        return;
+    };
+    let grandparent = parent_element(&parent);
+    for (ancestor, divider) in [(Some(parent), 1.0), (grandparent, 2.0)] {
+        let Some(ancestor) = ancestor else {
+            continue;
+        };
+        let key = Rc::as_ptr(&ancestor);
+        let i = *index.entry(key).or_insert_with(|| {
+            candidates.push(Candidate {
+                score: initial_score(&ancestor),
+                handle: ancestor.clone(),
+            });
+            candidates.len() - 1
+        });
+        candidates[i].score += score / divider;
+    }
+}
+
+/// Base score of a candidate from its tag and class/id hints.
+fn initial_score(handle: &Handle) -> f64 {
+    let tag_score = match handle.data {
+        NodeData::Element { ref name, .. } => match name.local.as_ref() {
+            "div" | "article" | "main" => 5.0,
+            "pre" | "td" | "blockquote" => 3.0,
+            "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+            _ => 0.0,
+        },
+        _ => 0.0,
+    };
+    tag_score + class_weight(handle)
+}
+
+/// ±25 for each of `class` and `id` that looks like content or boilerplate.
+fn class_weight(handle: &Handle) -> f64 {
+    let mut weight = 0.0;
+    for attr in ["class", "id"] {
+        if let Some(value) = get_attr(handle, attr).filter(|v| !v.is_empty()) {
+            if NEGATIVE.is_match(&value) {
+                weight -= 25.0;
+            }
+            if POSITIVE.is_match(&value) {
+                weight += 25.0;
+            }
+        }
+    }
+    weight
+}
+
+fn is_unlikely(handle: &Handle) -> bool {
+    if is_tag(handle, "body") || is_tag(handle, "a") || is_tag(handle, "article") {
+        return false;
+    }
+    if get_attr(handle, "role").is_some_and(|role| {
+        matches!(
+            role.as_str(),
+            "menu"
+                | "menubar"
+                | "complementary"
+                | "navigation"
+                | "alert"
+                | "alertdialog"
+                | "dialog"
+        )
+    }) {
+        return true;
+    }
+    let hints = format!(
+        "{} {}",
+        get_attr(handle, "class").unwrap_or_default(),
+        get_attr(handle, "id").unwrap_or_default()
+    );
+    UNLIKELY.is_match(&hints) && !MAYBE.is_match(&hints)
+}
+
+/// A `<div>` holding only inline content, which Readability treats as a `<p>`.
+fn is_leaf_div(handle: &Handle) -> bool {
+    is_tag(handle, "div")
+        && !handle.children.borrow().iter().any(|child| {
+            matches!(child.data, NodeData::Element { ref name, .. }
+            if matches!(
+                name.local.as_ref(),
+                "a" | "blockquote" | "dl" | "div" | "img" | "ol" | "p" | "pre" | "table" | "ul"
+                    | "section" | "article" | "figure" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+            ))
+        })
+}
+
+/// Text of all descendant text nodes, skipping scripts and styles.
+fn inner_text(handle: &Handle) -> String {
+    let mut text = String::new();
+    collect(handle, &mut text, 0);
+    text
+}
+
+fn collect(handle: &Handle, text: &mut String, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    match handle.data {
+        NodeData::Text { ref contents } => text.push_str(&contents.borrow()),
+        NodeData::Element { ref name, .. }
+            if matches!(
+                name.local.as_ref(),
+                "script" | "style" | "noscript" | "template"
+            ) => {}
+        _ => {
+            for child in handle.children.borrow().iter() {
+                collect(child, text, depth + 1);
+            }
+        }
+    }
+}
+
+/// Number of non-whitespace characters of text.
+fn text_len(handle: &Handle) -> usize {
+    inner_text(handle)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .count()
+}
+
+/// Share of the element's text that sits inside links.
+fn link_density(handle: &Handle) -> f64 {
+    let total = text_len(handle);
+    if total == 0 {
+        return 0.0;
+    }
+    let mut links = 0;
+    link_text_len(handle, &mut links, 0);
+    links as f64 / total as f64
+}
+
+fn link_text_len(handle: &Handle, len: &mut usize, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    for child in handle.children.borrow().iter() {
+        if is_tag(child, "a") {
+            // In-page anchors are navigation within the article itself.
+            let weight = if get_attr(child, "href").is_some_and(|h| h.starts_with('#')) {
+                0.3
+            } else {
+                1.0
+            };
+            *len += (text_len(child) as f64 * weight) as usize;
+        } else {
+            link_text_len(child, len, depth + 1);
+        }
+    }
+}
+
+fn has_sentence_end(handle: &Handle) -> bool {
+    let text = inner_text(handle);
+    let text = text.trim_end();
+    text.ends_with('.') || text.contains(". ")
+}
+
+fn body(document: &Handle) -> Option<Handle> {
+    let html = document
+        .children
+        .borrow()
+        .iter()
+        .find(|c| is_tag(c, "html"))
+        .cloned()?;
+    let body = html
+        .children
+        .borrow()
+        .iter()
+        .find(|c| is_tag(c, "body"))
+        .cloned();
+    body
+}
@@ -0,0 +1,217 @@
+// Reference-style links and images.
+//
+// Post-processing pass over the finished MDAST: `Link`/`Image` nodes become
+// `LinkReference`/`ImageReference` nodes with full (`[text][1]`) references,
+// and the matching `Definition`s are inserted at the end of the document or
+// at the end of each top-level section.
+
+use std::collections::HashMap;
+
+use crate::mdast::{self, Node, ReferenceKind};
+
+/// How links and images are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkStyle {
+    /// `[text](url)` (default).
+    #[default]
+    Inline,
+    /// `[text][1]` with `[1]: url` definitions collected elsewhere.
+    Reference,
+}
+
+/// Where reference definitions go when [`LinkStyle::Reference`] is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefinitionPlacement {
+    /// All definitions at the end of the document (default).
+    #[default]
+    EndOfDocument,
+    /// Definitions after the content of each top-level section, i.e. before
+    /// each root-level heading of the shallowest depth used for sections.
+    /// A single leading title heading does not start a section of its own.
+    EndOfSection,
+}
+
+/// Assigns one identifier per distinct `(url, title)`, numbered in order of
+/// first use, so the output is deterministic and repeated targets share a
+/// definition.
+#[derive(Default)]
+struct Registry {
+    ids: HashMap<(String, Option<String>), String>,
+}
+
+impl Registry {
+    /// Identifier for the target, plus its definition if it is new.
+    fn identify(
+        &mut self,
+        url: &str,
+        title: &Option<String>,
+    ) -> (String, Option<mdast::Definition>) {
+        let key = (url.to_string(), title.clone());
+        if let Some(id) = self.ids.get(&key) {
+            return (id.clone(), None);
+        }
+        let id = (self.ids.len() + 1).to_string();
+        self.ids.insert(key, id.clone());
+        let definition = mdast::Definition {
+            identifier: id.clone(),
+            label: None,
+            url: url.to_string(),
+            title: title.clone(),
+        };
+        (id, Some(definition))
+    }
+}
+
+/// Rewrite links and images in `children` (the root's children) as
+/// references and insert their definitions.
+pub(crate) fn to_references(children: &mut Vec<Node>, placement: DefinitionPlacement) {
+    let mut registry = Registry::default();
+
+    // Trailing footnote definitions stay last; their links are defined with
+    // the final section.
+    let tail = children.len()
+        - children
+            .iter()
+            .rev()
+            .take_while(|n| matches!(n, Node::FootnoteDefinition(_)))
+            .count();
+
+    let boundaries = match placement {
+        DefinitionPlacement::EndOfDocument => Vec::new(),
+        DefinitionPlacement::EndOfSection => section_starts(&children[..tail]),
+    };
+
+    // Split into sections, rewrite each, and reassemble with definitions.
+    let mut rest: Vec<Node> = std::mem::take(children);
+    let mut footnotes = rest.split_off(tail);
+    let mut sections = Vec::new();
+    for &start in boundaries.iter().rev() {
+        sections.push(rest.split_off(start));
+    }
+    sections.push(rest);
+    sections.reverse();
+
+    let last = sections.len() - 1;
+    for (i, mut section) in sections.into_iter().enumerate() {
+        let mut definitions = Vec::new();
+        rewrite(&mut section, &mut registry, &mut definitions);
+        if i == last {
+            rewrite(&mut footnotes, &mut registry, &mut definitions);
+        }
+        children.append(&mut section);
+        children.extend(definitions.into_iter().map(Node::Definition));
+    }
+    children.append(&mut footnotes);
+}
+
+/// Indices of root-level headings that start a top-level section.
+fn section_starts(children: &[Node]) -> Vec<usize> {
+    let headings: Vec<(usize, u8)> = children
+        .iter()
+        .enumerate()
+        .filter_map(|(i, n)| match n {
+            Node::Heading(h) => Some((i, h.depth)),
+            _ => None,
+        })
+        .collect();
+    let Some(mut depth) = headings.iter().map(|&(_, d)| d).min() else {
+        return Vec::new();
+    };
+    // A lone title heading at the top: use the next level for sections.
+    let at_min: Vec<usize> = headings
+        .iter()
+        .filter(|&&(_, d)| d == depth)
+        .map(|&(i, _)| i)
+        .collect();
+    if at_min.len() == 1 && headings[0].0 == at_min[0] {
+        if let Some(next) = headings
+            .iter()
+            .map(|&(_, d)| d)
+            .filter(|&d| d > depth)
+            .min()
+        {
+            depth = next;
+        }
+    }
+    headings
+        .into_iter()
+        .filter(|&(i, d)| d == depth && i > 0)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn rewrite(nodes: &mut [Node], registry: &mut Registry, definitions: &mut Vec<mdast::Definition>) {
+    for node in nodes.iter_mut() {
+        let replacement = match node {
+            Node::Link(link) if should_reference(link) => {
+                let (identifier, definition) = registry.identify(&link.url, &link.title);
+                definitions.extend(definition);
+                Some(Node::LinkReference(mdast::LinkReference {
+                    identifier,
+                    label: None,
+                    reference_kind: ReferenceKind::Full,
+                    children: std::mem::take(&mut link.children),
+                }))
+            }
+            Node::Image(image) if !image.url.is_empty() => {
+                let (identifier, definition) = registry.identify(&image.url, &image.title);
+                definitions.extend(definition);
+                Some(Node::ImageReference(mdast::ImageReference {
+                    identifier,
+                    label: None,
+                    reference_kind: ReferenceKind::Full,
+                    alt: std::mem::take(&mut image.alt),
+                }))
+            }
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            *node = replacement;
+        }
+        if let Some(children) = node.children_mut() {
+            rewrite(children, registry, definitions);
+        }
+    }
+}
+
+/// Links that are clearer inline: empty and in-page targets, and links whose
+/// text is the URL itself (written as `<url>` autolinks).
+fn should_reference(link: &mdast::Link) -> bool {
+    if link.url.is_empty() || link.url.starts_with('#') {
+        return false;
+    }
+    let is_autolink = link.title.is_none()
+        && matches!(link.children.as_slice(), [Node::Text(t)]
+            if t.value == link.url || format!("mailto:{}", t.value) == link.url);
+    !is_autolink
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(depth: u8) -> Node {
+        Node::Heading(mdast::Heading {
+            depth,
+            children: vec![],
+            attributes: mdast::Attributes::default(),
+        })
+    }
+
+    fn para() -> Node {
+        Node::Paragraph(mdast::Paragraph { children: vec![] })
+    }
+
+    #[test]
+    fn sections_split_at_shallowest_heading() {
+        let nodes = [para(), heading(2), para(), heading(3), heading(2), para()];
+        assert_eq!(section_starts(&nodes), [1, 4]);
+    }
+
+    #[test]
+    fn lone_title_heading_is_not_a_section() {
+        let nodes = [heading(1), para(), heading(2), para(), heading(2)];
+        assert_eq!(section_starts(&nodes), [2, 4]);
+        assert_eq!(section_starts(&[heading(1), para()]), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,606 @@
+// CSS selector parsing and matching against the html5ever tree.
+//
+// Supports the subset of Selectors Level 3 that is useful for picking content
+// out of scraped pages: type and universal selectors, `#id`, `.class`,
+// attribute selectors (`[a]`, `=`, `~=`, `|=`, `^=`, `$=`, `*=`, with an
+// optional `i` flag), `:not(…)` over compound selectors, the four combinators
+// (descendant, `>`, `+`, `~`), and comma-separated selector lists.
+// Pseudo-elements and other pseudo-classes are rejected at parse time.
+
+use std::fmt;
+use std::str::FromStr;
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+/// Error returned when a CSS selector cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid selector {selector:?}: {reason}")]
+pub struct SelectorError {
+    selector: String,
+    reason: String,
+}
+
+/// A parsed CSS selector (or comma-separated selector list).
+///
+/// ```
+/// use html2markdown::Selector;
+///
+/// let selector = Selector::parse("article .post-body, main > p:not(.ad)").unwrap();
+/// assert_eq!(selector.as_str(), "article .post-body, main > p:not(.ad)");
+/// assert!(Selector::parse("p::before").is_err());
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Selector {
+    source: String,
+    alternatives: Vec<Complex>,
+}
+
+/// Compound selectors joined by combinators, e.g. `article > .body p`.
+/// `combinators[i]` sits between `compounds[i]` and `compounds[i + 1]`.
+#[derive(Debug, Clone, PartialEq)]
+struct Complex {
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// `a b`
+    Descendant,
+    /// `a > b`
+    Child,
+    /// `a + b`
+    NextSibling,
+    /// `a ~ b`
+    SubsequentSibling,
+}
+
+/// A sequence of simple selectors with no combinator, e.g. `div.note#x`.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Compound {
+    /// Lowercase tag name; `None` for `*` or an omitted type selector.
+    tag: Option<String>,
+    simple: Vec<Simple>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Simple {
+    Id(String),
+    Class(String),
+    Attr {
+        name: String,
+        op: Option<(AttrOp, String)>,
+        case_insensitive: bool,
+    },
+    Not(Vec<Compound>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttrOp {
+    /// `[a=v]`
+    Equals,
+    /// `[a~=v]` — whitespace-separated word.
+    Includes,
+    /// `[a|=v]` — exactly `v` or prefixed by `v-`.
+    DashMatch,
+    /// `[a^=v]`
+    Prefix,
+    /// `[a$=v]`
+    Suffix,
+    /// `[a*=v]`
+    Substring,
+}
+
+impl Selector {
+    /// Parse a selector or comma-separated selector list.
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let mut parser = Parser {
+            source: selector,
+            chars: selector.char_indices().peekable(),
+        };
+        let alternatives = parser.selector_list()?;
+        Ok(Self {
+            source: selector.trim().to_string(),
+            alternatives,
+        })
+    }
+
+    /// The selector text as written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the element `handle` matches this selector.
+    pub(crate) fn matches(&self, handle: &Handle) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| matches_complex(complex, complex.compounds.len() - 1, handle))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Selector::parse(s)
+    }
+}
+
+impl fmt::Debug for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Selector").field(&self.source).finish()
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: impl Into<String>) -> SelectorError {
+        SelectorError {
+            selector: self.source.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, c)| c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skip whitespace; returns whether any was skipped.
+    fn skip_whitespace(&mut self) -> bool {
+        let mut skipped = false;
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.bump();
+            skipped = true;
+        }
+        skipped
+    }
+
+    fn selector_list(&mut self) -> Result<Vec<Complex>, SelectorError> {
+        let mut list = vec![self.complex()?];
+        while self.eat(',') {
+            list.push(self.complex()?);
+        }
+        match self.peek() {
+            None => Ok(list),
+            Some(c) => Err(self.error(format!("unexpected {c:?}"))),
+        }
+    }
+
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_whitespace();
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = Vec::new();
+        loop {
+            let had_space = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None | Some(',') | Some(')') => break,
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::NextSibling,
+                Some('~') => Combinator::SubsequentSibling,
+                _ if had_space => Combinator::Descendant,
+                Some(c) => return Err(self.error(format!("unexpected {c:?}"))),
+            };
+            if combinator != Combinator::Descendant {
+                self.bump();
+                self.skip_whitespace();
+            }
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+        Ok(Complex {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let mut compound = Compound::default();
+        let mut empty = true;
+
+        if self.eat('*') {
+            empty = false;
+        } else if self.peek().is_some_and(is_ident_start) {
+            compound.tag = Some(self.ident()?.to_ascii_lowercase());
+            empty = false;
+        }
+
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.bump();
+                    compound.simple.push(Simple::Id(self.ident()?));
+                }
+                Some('.') => {
+                    self.bump();
+                    compound.simple.push(Simple::Class(self.ident()?));
+                }
+                Some('[') => {
+                    self.bump();
+                    compound.simple.push(self.attribute()?);
+                }
+                Some(':') => {
+                    self.bump();
+                    if self.peek() == Some(':') {
+                        return Err(self.error("pseudo-elements are not supported"));
+                    }
+                    let name = self.ident()?.to_ascii_lowercase();
+                    if name != "not" || !self.eat('(') {
+                        return Err(self.error(format!("unsupported pseudo-class :{name}")));
+                    }
+                    let mut inner = Vec::new();
+                    loop {
+                        self.skip_whitespace();
+                        inner.push(self.compound()?);
+                        self.skip_whitespace();
+                        if !self.eat(',') {
+                            break;
+                        }
+                    }
+                    if !self.eat(')') {
+                        return Err(self.error(":not() takes compound selectors only"));
+                    }
+                    compound.simple.push(Simple::Not(inner));
+                }
+                _ => break,
+            }
+            empty = false;
+        }
+
+        if empty {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("expected a selector, found {c:?}")),
+                None => self.error("expected a selector"),
+            });
+        }
+        Ok(compound)
+    }
+
+    fn attribute(&mut self) -> Result<Simple, SelectorError> {
+        self.skip_whitespace();
+        let name = self.ident()?.to_ascii_lowercase();
+        self.skip_whitespace();
+        let op = match self.peek() {
+            Some(']') => None,
+            Some('=') => Some(AttrOp::Equals),
+            Some(c @ ('~' | '|' | '^' | '$' | '*')) => {
+                self.bump();
+                if self.peek() != Some('=') {
+                    return Err(self.error(format!("expected '=' after {c:?}")));
+                }
+                Some(match c {
+                    '~' => AttrOp::Includes,
+                    '|' => AttrOp::DashMatch,
+                    '^' => AttrOp::Prefix,
+                    '$' => AttrOp::Suffix,
+                    _ => AttrOp::Substring,
+                })
+            }
+            _ => return Err(self.error("malformed attribute selector")),
+        };
+
+        let mut case_insensitive = false;
+        let op = match op {
+            None => None,
+            Some(op) => {
+                self.bump(); // '='
+                self.skip_whitespace();
+                let value = match self.peek() {
+                    Some(quote @ ('"' | '\'')) => {
+                        self.bump();
+                        self.string(quote)?
+                    }
+                    _ => self.ident()?,
+                };
+                self.skip_whitespace();
+                if matches!(self.peek(), Some('i' | 'I')) {
+                    self.bump();
+                    self.skip_whitespace();
+                    case_insensitive = true;
+                }
+                Some((op, value))
+            }
+        };
+
+        if !self.eat(']') {
+            return Err(self.error("unterminated attribute selector"));
+        }
+        Ok(Simple::Attr {
+            name,
+            op,
+            case_insensitive,
+        })
+    }
+
+    fn ident(&mut self) -> Result<String, SelectorError> {
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.bump();
+                match self.bump() {
+                    Some(escaped) => ident.push(escaped),
+                    None => return Err(self.error("trailing backslash")),
+                }
+            } else if is_ident_char(c) {
+                ident.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if ident.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("expected an identifier, found {c:?}")),
+                None => self.error("expected an identifier"),
+            });
+        }
+        Ok(ident)
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, SelectorError> {
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => return Ok(value),
+                Some('\\') => match self.bump() {
+                    Some(escaped) => value.push(escaped),
+                    None => break,
+                },
+                Some(c) => value.push(c),
+                None => break,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '-' || c == '\\' || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+// ---------------------------------------------------------------------------
+// Matching
+// ---------------------------------------------------------------------------
+
+/// Match `complex.compounds[..=index]` right-to-left, anchored at `handle`.
+fn matches_complex(complex: &Complex, index: usize, handle: &Handle) -> bool {
+    if !matches_compound(&complex.compounds[index], handle) {
+        return false;
+    }
+    if index == 0 {
+        return true;
+    }
+    match complex.combinators[index - 1] {
+        Combinator::Child => {
+            parent_element(handle).is_some_and(|p| matches_complex(complex, index - 1, &p))
+        }
+        Combinator::Descendant => {
+            let mut current = parent_element(handle);
+            while let Some(ancestor) = current {
+                if matches_complex(complex, index - 1, &ancestor) {
+                    return true;
+                }
+                current = parent_element(&ancestor);
+            }
+            false
+        }
+        Combinator::NextSibling => previous_element_siblings(handle)
+            .first()
+            .is_some_and(|s| matches_complex(complex, index - 1, s)),
+        Combinator::SubsequentSibling => previous_element_siblings(handle)
+            .iter()
+            .any(|s| matches_complex(complex, index - 1, s)),
+    }
+}
+
+fn matches_compound(compound: &Compound, handle: &Handle) -> bool {
+    let NodeData::Element {
+        ref name,
+        ref attrs,
+        ..
+    } = handle.data
+    else {
+        return false;
+    };
+    if let Some(tag) = &compound.tag {
+        if name.local.as_ref() != tag {
+            return false;
+        }
+    }
+    let attrs = attrs.borrow();
+    let attr = |wanted: &str| {
+        attrs
+            .iter()
+            .find(|a| a.name.local.as_ref() == wanted)
+            .map(|a| a.value.as_ref())
+    };
+    compound.simple.iter().all(|simple| match simple {
+        Simple::Id(id) => attr("id") == Some(id.as_str()),
+        Simple::Class(class) => {
+            attr("class").is_some_and(|v| v.split_ascii_whitespace().any(|c| c == class))
+        }
+        Simple::Attr {
+            name,
+            op,
+            case_insensitive,
+        } => match (attr(name), op) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(actual), Some((op, expected))) => {
+                if *case_insensitive {
+                    let actual = actual.to_lowercase();
+                    matches_attr(&actual, *op, &expected.to_lowercase())
+                } else {
+                    matches_attr(actual, *op, expected)
+                }
+            }
+        },
+        Simple::Not(inner) => !inner.iter().any(|c| matches_compound(c, handle)),
+    })
+}
+
+fn matches_attr(actual: &str, op: AttrOp, expected: &str) -> bool {
+    match op {
+        AttrOp::Equals => actual == expected,
+        AttrOp::Includes => actual.split_ascii_whitespace().any(|w| w == expected),
+        AttrOp::DashMatch => {
+            actual == expected
+                || actual
+                    .strip_prefix(expected)
+                    .is_some_and(|rest| rest.starts_with('-'))
+        }
+        AttrOp::Prefix => !expected.is_empty() && actual.starts_with(expected),
+        AttrOp::Suffix => !expected.is_empty() && actual.ends_with(expected),
+        AttrOp::Substring => !expected.is_empty() && actual.contains(expected),
+    }
+}
+
+/// The parent of `handle`, if it is an element (not the document).
+pub(crate) fn parent_element(handle: &Handle) -> Option<Handle> {
+    let weak = handle.parent.take();
+    let parent = weak.as_ref().and_then(|w| w.upgrade());
+    handle.parent.set(weak);
+    parent.filter(|p| matches!(p.data, NodeData::Element { .. }))
+}
+
+/// Element siblings before `handle`, nearest first.
+fn previous_element_siblings(handle: &Handle) -> Vec<Handle> {
+    let weak = handle.parent.take();
+    let parent = weak.as_ref().and_then(|w| w.upgrade());
+    handle.parent.set(weak);
+    let Some(parent) = parent else {
+        return Vec::new();
+    };
+    let children = parent.children.borrow();
+    let position = children
+        .iter()
+        .position(|c| std::rc::Rc::ptr_eq(c, handle))
+        .unwrap_or(0);
+    children[..position]
+        .iter()
+        .rev()
+        .filter(|c| matches!(c.data, NodeData::Element { .. }))
+        .cloned()
+        .collect()
+}
+
+/// Collect the outermost elements under `handle` that match `selector`, in
+/// document order. Matches nested inside another match are not returned
+/// separately — they are converted as part of their ancestor.
+pub(crate) fn select_outermost(handle: &Handle, selector: &Selector) -> Vec<Handle> {
+    let mut found = Vec::new();
+    select_outermost_inner(handle, selector, &mut found, 0);
+    found
+}
+
+fn select_outermost_inner(
+    handle: &Handle,
+    selector: &Selector,
+    found: &mut Vec<Handle>,
+    depth: usize,
+) {
+    if depth >= super::MAX_DEPTH {
+        return;
+    }
+    for child in handle.children.borrow().iter() {
+        if !matches!(child.data, NodeData::Element { .. }) {
+            continue;
+        }
+        if selector.matches(child) {
+            found.push(child.clone());
+        } else {
+            select_outermost_inner(child, selector, found, depth + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_match(html: &str, selector: &str) -> Vec<String> {
+        let dom = super::super::parse_html(html);
+        let selector = Selector::parse(selector).unwrap();
+        select_outermost(&dom.document, &selector)
+            .iter()
+            .map(|h| super::super::handlers::to_text(h).trim().replace('\n', " "))
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_rejects() {
+        assert!(Selector::parse("div.a#b[c~='d' i] > p + span ~ em").is_ok());
+        assert!(Selector::parse("a:not(.x, [href^=http])").is_ok());
+        assert!(Selector::parse("").is_err());
+        assert!(Selector::parse("p,").is_err());
+        assert!(Selector::parse("a::after").is_err());
+        assert!(Selector::parse("li:first-child").is_err());
+        assert!(Selector::parse("[href").is_err());
+    }
+
+    #[test]
+    fn error_message_names_selector() {
+        let err = Selector::parse("div >").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid selector \"div >\": expected a selector"
+        );
+    }
+
+    #[test]
+    fn descendant_and_child_combinators() {
+        let html = "<article><div><p class=x>a</p></div><p class=x>b</p></article><p class=x>c</p>";
+        assert_eq!(first_match(html, "article .x"), ["a", "b"]);
+        assert_eq!(first_match(html, "article > .x"), ["b"]);
+        assert_eq!(first_match(html, "div + p"), ["b"]);
+        assert_eq!(first_match(html, "article ~ p"), ["c"]);
+    }
+
+    #[test]
+    fn attribute_operators() {
+        let html = r#"<a href="https://x.test/a" lang="en-US" rel="nofollow noopener">1</a><a href="/b">2</a>"#;
+        assert_eq!(first_match(html, "[href^=https]"), ["1"]);
+        assert_eq!(first_match(html, "[href$='/b']"), ["2"]);
+        assert_eq!(first_match(html, "[href*=x]"), ["1"]);
+        assert_eq!(first_match(html, "[rel~=noopener]"), ["1"]);
+        assert_eq!(first_match(html, "[lang|=en]"), ["1"]);
+        assert_eq!(first_match(html, "[REL~=NOFOLLOW i]"), ["1"]);
+        assert_eq!(first_match(html, "a:not([rel])"), ["2"]);
+    }
+
+    #[test]
+    fn outermost_only() {
+        let html = "<div class=c>a<div class=c>b</div></div>";
+        assert_eq!(first_match(html, ".c"), ["a b"]);
+    }
+}
@@ -0,0 +1,356 @@
+// Structured data extraction: JSON-LD and microdata.
+//
+// JSON-LD blocks (`<script type="application/ld+json">`) are parsed as-is.
+// Microdata items (`itemscope`/`itemprop`) are collected following the HTML
+// microdata algorithm and represented in the WHATWG "JSON for microdata"
+// shape: `{"type": [...], "id": "...", "properties": {"name": [values]}}`.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use markup5ever_rcdom::{Handle, NodeData};
+use serde_json::{Map, Value};
+
+use super::handlers::{get_attr, has_attr};
+use super::util::normalize_whitespace;
+use super::{State, MAX_DEPTH};
+
+/// Structured data found in the document, as [`serde_json`] values
+/// (re-exported as `html2markdown::serde_json`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructuredData {
+    /// Parsed JSON-LD blocks, in document order. Blocks that fail to parse
+    /// are skipped.
+    pub json_ld: Vec<Value>,
+    /// Top-level microdata items, in document order.
+    pub microdata: Vec<Value>,
+}
+
+impl StructuredData {
+    /// Whether no structured data was found.
+    pub fn is_empty(&self) -> bool {
+        self.json_ld.is_empty() && self.microdata.is_empty()
+    }
+
+    /// Look up a dotted property path (e.g. `"headline"`, `"author.name"`)
+    /// in the first JSON-LD node, then the first microdata item, that has it.
+    ///
+    /// JSON-LD arrays and `@graph` containers are searched node by node. When
+    /// the path crosses an array (several authors), the matches are collected
+    /// into an array.
+    ///
+    /// ```
+    /// use html2markdown::serde_json::json;
+    /// use html2markdown::{html_to_document, Options};
+    ///
+    /// let html = r#"<script type="application/ld+json">
+    ///   {"@type": "Article", "headline": "Hi", "author": [{"name": "A"}, {"name": "B"}]}
+    /// </script>"#;
+    /// let data = html_to_document(html, &Options::new()).structured_data;
+    /// assert_eq!(data.find("headline"), Some("Hi".into()));
+    /// assert_eq!(data.find("author.name"), Some(json!(["A", "B"])));
+    /// ```
+    pub fn find(&self, path: &str) -> Option<Value> {
+        let path: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+        if path.is_empty() {
+            return None;
+        }
+        let mut nodes = Vec::new();
+        for block in &self.json_ld {
+            flatten_json_ld(block, &mut nodes);
+        }
+        let microdata: Vec<Value> = self.microdata.iter().map(microdata_as_object).collect();
+        nodes
+            .into_iter()
+            .chain(microdata.iter())
+            .find_map(|node| lookup(node, &path))
+    }
+}
+
+/// Collect JSON-LD blocks and microdata items from the document.
+pub(crate) fn extract(state: &State, document: &Handle) -> StructuredData {
+    let mut data = StructuredData::default();
+    collect(state, document, &mut data, 0);
+    data
+}
+
+fn collect(state: &State, handle: &Handle, data: &mut StructuredData, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if let NodeData::Element { ref name, .. } = handle.data {
+        if name.local.as_ref() == "script" {
+            if is_json_ld(handle) {
+                if let Some(value) = parse_json_ld(&raw_text(handle)) {
+                    data.json_ld.push(value);
+                }
+            }
+            return;
+        }
+        if has_attr(handle, "itemscope") && !has_attr(handle, "itemprop") {
+            let mut visited = HashSet::new();
+            data.microdata.push(item(state, handle, &mut visited, 0));
+        }
+    }
+    for child in handle.children.borrow().iter() {
+        collect(state, child, data, depth + 1);
+    }
+}
+
+fn is_json_ld(handle: &Handle) -> bool {
+    get_attr(handle, "type").is_some_and(|t| {
+        t.split(';')
+            .next()
+            .is_some_and(|t| t.trim().eq_ignore_ascii_case("application/ld+json"))
+    })
+}
+
+/// Parse a JSON-LD block, tolerating the HTML comment and CDATA wrappers
+/// some CMSes put around script bodies.
+fn parse_json_ld(text: &str) -> Option<Value> {
+    let mut text = text.trim();
+    for (open, close) in [("<!--", "-->"), ("<![CDATA[", "]]>")] {
+        if let Some(inner) = text.strip_prefix(open).and_then(|t| t.strip_suffix(close)) {
+            text = inner.trim();
+        }
+    }
+    serde_json::from_str(text).ok()
+}
+
+// ---------------------------------------------------------------------------
+// Microdata
+// ---------------------------------------------------------------------------
+
+/// Build the JSON object for the item rooted at `handle`.
+fn item(state: &State, handle: &Handle, visited: &mut HashSet<usize>, depth: usize) -> Value {
+    visited.insert(Rc::as_ptr(handle) as usize);
+    let mut object = Map::new();
+
+    let types: Vec<Value> = get_attr(handle, "itemtype")
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+        .map(|t| Value::String(t.to_string()))
+        .collect();
+    if !types.is_empty() {
+        object.insert("type".to_string(), Value::Array(types));
+    }
+    if let Some(id) = get_attr(handle, "itemid").filter(|id| !id.trim().is_empty()) {
+        object.insert("id".to_string(), Value::String(state.resolve(id.trim())));
+    }
+
+    // Property elements: descendants up to nested items, plus `itemref`s.
+    let mut roots: Vec<Handle> = handle.children.borrow().iter().cloned().collect();
+    for id in get_attr(handle, "itemref")
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+    {
+        if let Some(target) = state.element_by_id.get(id) {
+            roots.push(target.clone());
+        }
+    }
+    let mut properties = Map::new();
+    for root in &roots {
+        collect_properties(state, root, &mut properties, visited, depth + 1);
+    }
+    object.insert("properties".to_string(), Value::Object(properties));
+    Value::Object(object)
+}
+
+fn collect_properties(
+    state: &State,
+    handle: &Handle,
+    properties: &mut Map<String, Value>,
+    visited: &mut HashSet<usize>,
+    depth: usize,
+) {
+    if depth >= MAX_DEPTH || !matches!(handle.data, NodeData::Element { .. }) {
+        return;
+    }
+    if let Some(names) = get_attr(handle, "itemprop") {
+        // Guard against `itemref` cycles.
+        if has_attr(handle, "itemscope") && visited.contains(&(Rc::as_ptr(handle) as usize)) {
+            return;
+        }
+        let value = property_value(state, handle, visited, depth);
+        for name in names.split_ascii_whitespace() {
+            let values = properties
+                .entry(name.to_string())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(values) = values {
+                values.push(value.clone());
+            }
+        }
+    }
+    // A nested item owns its own descendants.
+    if has_attr(handle, "itemscope") {
+        return;
+    }
+    for child in handle.children.borrow().iter() {
+        collect_properties(state, child, properties, visited, depth + 1);
+    }
+}
+
+/// The value of a property element, per the microdata spec.
+fn property_value(
+    state: &State,
+    handle: &Handle,
+    visited: &mut HashSet<usize>,
+    depth: usize,
+) -> Value {
+    if has_attr(handle, "itemscope") {
+        return item(state, handle, visited, depth);
+    }
+    let attr = |name: &str| get_attr(handle, name).unwrap_or_default();
+    let url = |name: &str| state.resolve(attr(name).trim());
+    let tag = match handle.data {
+        NodeData::Element { ref name, .. } => name.local.as_ref(),
+        _ => "",
+    };
+    let value = match tag {
+        "meta" => attr("content"),
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => url("src"),
+        "a" | "area" | "link" => url("href"),
+        "object" => url("data"),
+        "data" | "meter" => attr("value"),
+        "time" if has_attr(handle, "datetime") => attr("datetime"),
+        _ => normalize_whitespace(&raw_text(handle)),
+    };
+    Value::String(value)
+}
+
+/// Reshape a microdata item like a JSON-LD node (`@type`, single values
+/// unwrapped) so `find` can walk both the same way.
+fn microdata_as_object(item: &Value) -> Value {
+    let mut object = Map::new();
+    if let Some(types) = item.get("type") {
+        object.insert("@type".to_string(), unwrap_single(types.clone()));
+    }
+    if let Some(id) = item.get("id") {
+        object.insert("@id".to_string(), id.clone());
+    }
+    if let Some(Value::Object(properties)) = item.get("properties") {
+        for (name, values) in properties {
+            let values = match values {
+                Value::Array(values) => values
+                    .iter()
+                    .map(|v| {
+                        if v.get("properties").is_some() {
+                            microdata_as_object(v)
+                        } else {
+                            v.clone()
+                        }
+                    })
+                    .collect(),
+                other => vec![other.clone()],
+            };
+            object.insert(name.clone(), unwrap_single(Value::Array(values)));
+        }
+    }
+    Value::Object(object)
+}
+
+// ---------------------------------------------------------------------------
+// Lookup
+// ---------------------------------------------------------------------------
+
+/// Push every JSON-LD node in `value`, descending into arrays and `@graph`.
+fn flatten_json_ld<'a>(value: &'a Value, nodes: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                flatten_json_ld(item, nodes);
+            }
+        }
+        Value::Object(object) => {
+            nodes.push(value);
+            if let Some(graph) = object.get("@graph") {
+                flatten_json_ld(graph, nodes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn lookup(value: &Value, path: &[&str]) -> Option<Value> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(value.clone()).filter(|v| !is_blank(v));
+    };
+    match value {
+        Value::Object(object) => lookup(object.get(*first)?, rest),
+        Value::Array(items) => {
+            let found: Vec<Value> = items.iter().filter_map(|v| lookup(v, path)).collect();
+            match found.len() {
+                0 => None,
+                _ => Some(unwrap_single(Value::Array(found))),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn unwrap_single(value: Value) -> Value {
+    match value {
+        Value::Array(mut items) if items.len() == 1 => items.remove(0),
+        other => other,
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Concatenated text of all descendant text nodes.
+fn raw_text(handle: &Handle) -> String {
+    let mut text = String::new();
+    raw_text_inner(handle, &mut text, 0);
+    text
+}
+
+fn raw_text_inner(handle: &Handle, text: &mut String, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if let NodeData::Text { ref contents } = handle.data {
+        text.push_str(&contents.borrow());
+    }
+    for child in handle.children.borrow().iter() {
+        raw_text_inner(child, text, depth + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_json_ld_strips_wrappers() {
+        assert_eq!(parse_json_ld(r#"<!-- {"a": 1} -->"#), Some(json!({"a": 1})));
+        assert_eq!(
+            parse_json_ld(r#"<![CDATA[{"a": 1}]]>"#),
+            Some(json!({"a": 1}))
+        );
+        assert_eq!(parse_json_ld("{not json"), None);
+    }
+
+    #[test]
+    fn find_searches_graph_and_microdata() {
+        let data = StructuredData {
+            json_ld: vec![
+                json!({"@graph": [{"@type": "WebSite"}, {"@type": "Article", "headline": "H"}]}),
+            ],
+            microdata: vec![
+                json!({"type": ["https://schema.org/Person"], "properties": {"name": ["Ann"]}}),
+            ],
+        };
+        assert_eq!(data.find("headline"), Some(json!("H")));
+        assert_eq!(data.find("name"), Some(json!("Ann")));
+        assert_eq!(data.find("missing"), None);
+        assert_eq!(data.find(""), None);
+    }
+}
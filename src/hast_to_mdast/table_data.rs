@@ -0,0 +1,206 @@
+// Tables as data.
+//
+// `handle_table` already expands spans and pads rows into a rectangular
+// grid; when tables are being collected, each outermost table is also
+// recorded here with its cells reduced to plain text, ready for CSV or
+// JSON Lines export.
+
+use markup5ever_rcdom::Handle;
+use serde_json::Value;
+
+use super::handlers::{is_tag, to_text};
+use super::util::normalize_whitespace;
+use crate::mdast::{self, AlignKind, Node};
+
+/// A table as a grid of plain-text cells.
+///
+/// Every row has [`TableData::columns`] cells. Cells covered by a
+/// `colspan`/`rowspan` are empty, or repeat the spanning cell's value with
+/// [`Options::with_table_duplicate_spans`](crate::Options::with_table_duplicate_spans).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableData {
+    /// Text of the `<caption>`, if any.
+    pub caption: Option<String>,
+    /// The header row, if the table has one (`<thead>` or `<th>` cells in
+    /// the first rows).
+    pub header: Option<Vec<String>>,
+    /// The data rows.
+    pub rows: Vec<Vec<String>>,
+    /// Alignment of each column.
+    pub align: Vec<Option<AlignKind>>,
+}
+
+impl TableData {
+    /// Number of columns.
+    pub fn columns(&self) -> usize {
+        self.align.len()
+    }
+
+    /// The table as CSV (RFC 4180): the header row (if any), then the data
+    /// rows, each line ending in CRLF.
+    ///
+    /// ```
+    /// use html2markdown::{extract_tables, Options};
+    ///
+    /// let html = "<table><tr><th>Name</th><th>Note</th></tr>\
+    ///             <tr><td>Ann</td><td>a, \"b\"</td></tr></table>";
+    /// let tables = extract_tables(html, &Options::new());
+    /// assert_eq!(tables[0].to_csv(), "Name,Note\r\nAnn,\"a, \"\"b\"\"\"\r\n");
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in self.header.iter().chain(&self.rows) {
+            let fields: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// The data rows as JSON Lines: one object per row keyed by header
+    /// name, in column order. Empty header names become `Column N`, and
+    /// repeated ones get a `_2`, `_3`, … suffix. Tables without a header
+    /// give one array per row.
+    ///
+    /// ```
+    /// use html2markdown::{extract_tables, Options};
+    ///
+    /// let html = "<table><tr><th>Name</th><th>Qty</th></tr>\
+    ///             <tr><td>Apple</td><td>3</td></tr></table>";
+    /// let tables = extract_tables(html, &Options::new());
+    /// assert_eq!(tables[0].to_json_lines(), "{\"Name\":\"Apple\",\"Qty\":\"3\"}\n");
+    /// ```
+    pub fn to_json_lines(&self) -> String {
+        let string = |s: &str| Value::String(s.to_string()).to_string();
+        let keys = self.header.as_ref().map(|header| json_keys(header));
+        let mut lines = String::new();
+        for row in &self.rows {
+            let values = row.iter().map(|cell| string(cell));
+            match &keys {
+                Some(keys) => {
+                    let members: Vec<String> = keys
+                        .iter()
+                        .zip(values)
+                        .map(|(key, value)| format!("{}:{value}", string(key)))
+                        .collect();
+                    lines.push_str(&format!("{{{}}}", members.join(",")));
+                }
+                None => {
+                    let values: Vec<String> = values.collect();
+                    lines.push_str(&format!("[{}]", values.join(",")));
+                }
+            }
+            lines.push('\n');
+        }
+        lines
+    }
+}
+
+/// Record a normalized table, whose first row is the header row (empty and
+/// inserted by `handle_table` when `headless`).
+pub(crate) fn from_table(handle: &Handle, table: &mdast::Table, headless: bool) -> TableData {
+    let mut rows: Vec<Vec<String>> = table
+        .children
+        .iter()
+        .filter_map(|row| match row {
+            Node::TableRow(row) => Some(row.children.iter().map(cell_text).collect()),
+            _ => None,
+        })
+        .collect();
+    let header = if rows.is_empty() {
+        None
+    } else {
+        Some(rows.remove(0))
+    };
+    let caption = handle
+        .children
+        .borrow()
+        .iter()
+        .find(|child| is_tag(child, "caption"))
+        .map(|caption| normalize_whitespace(&to_text(caption)))
+        .filter(|caption| !caption.is_empty());
+    TableData {
+        caption,
+        header: header.filter(|_| !headless),
+        rows,
+        align: table.align.clone(),
+    }
+}
+
+/// Plain text of a cell: breaks become newlines, images their alt text,
+/// and whitespace is collapsed within each line.
+fn cell_text(cell: &Node) -> String {
+    fn collect(nodes: &[Node], text: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(t) => text.push_str(&t.value),
+                Node::InlineCode(c) => text.push_str(&c.value),
+                Node::Image(image) => text.push_str(&image.alt),
+                Node::Break(_) => text.push('\n'),
+                Node::InlineHtml(html) if html.value == "<br>" => text.push('\n'),
+                _ => {
+                    if let Some(children) = node.children() {
+                        collect(children, text);
+                    }
+                }
+            }
+        }
+    }
+    let mut text = String::new();
+    if let Some(children) = cell.children() {
+        collect(children, &mut text);
+    }
+    let lines: Vec<String> = text
+        .lines()
+        .map(normalize_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.join("\n")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Unique, non-empty object keys for a header row.
+fn json_keys(header: &[String]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::with_capacity(header.len());
+    for (i, name) in header.iter().enumerate() {
+        let base = if name.is_empty() {
+            format!("Column {}", i + 1)
+        } else {
+            name.clone()
+        };
+        let mut key = base.clone();
+        let mut n = 1;
+        while keys.contains(&key) {
+            n += 1;
+            key = format!("{base}_{n}");
+        }
+        keys.push(key);
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quotes_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn json_keys_are_unique() {
+        let header = ["Name", "", "Name", "Name"].map(String::from);
+        assert_eq!(json_keys(&header), ["Name", "Column 2", "Name_2", "Name_3"]);
+    }
+}
@@ -0,0 +1,617 @@
+// Table lossiness check and raw HTML fallback.
+//
+// GFM tables have one header row, one line per cell, and no spans. Tables
+// that need more are either flattened (the default, matching
+// hast-util-to-mdast) or, with `TableStrategy::HtmlFallback`, kept as a
+// cleaned-up HTML block: only table, phrasing and `<pre>` tags, presentational
+// attributes and script URLs dropped, spans and links kept, whitespace
+// collapsed.
+//
+// With `table_cell_breaks`, block content in cells is flattened to
+// lines separated by hard breaks, which the serializer writes as `<br>`,
+// instead of being run together.
+
+use markup5ever_rcdom::{Handle, NodeData};
+
+use super::handlers::{get_attr, is_tag};
+use super::util::{escape, is_whitespace_only};
+use super::{State, MAX_DEPTH};
+use crate::mdast::{self, Node};
+
+/// How tables are converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStrategy {
+    /// Always write a GFM table, flattening spans and nested content
+    /// (default).
+    #[default]
+    Gfm,
+    /// Write a GFM table when that is lossless; otherwise keep the table as
+    /// a raw HTML block of table and phrasing tags, with other elements
+    /// unwrapped and `javascript:`/`vbscript:`/`data:` URLs dropped.
+    HtmlFallback,
+}
+
+/// What happens to a table's `<caption>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableCaption {
+    /// Drop it (default, matching hast-util-to-mdast).
+    #[default]
+    Drop,
+    /// Write it as a paragraph before the table.
+    Paragraph,
+    /// Write it as an emphasized paragraph before the table.
+    Emphasis,
+}
+
+/// A reason a table cannot be written as a GFM table without losing
+/// structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableLoss {
+    /// A cell spans several columns (`colspan`).
+    ColSpan,
+    /// A cell spans several rows (`rowspan`).
+    RowSpan,
+    /// A cell contains another table.
+    NestedTable,
+    /// A cell contains block content: lists, code blocks, headings, block
+    /// quotes, rules, or several paragraphs.
+    BlockContent,
+    /// The header has more than one row.
+    MultipleHeaderRows,
+}
+
+/// Why `table` cannot be a lossless GFM table, in [`TableLoss`] declaration
+/// order without repeats. Empty when it can (or `table` is not a table).
+pub(crate) fn losses(table: &Handle) -> Vec<TableLoss> {
+    let mut found = Vec::new();
+    if !is_tag(table, "table") {
+        return found;
+    }
+    scan(table, &mut found, false, 0);
+    let header_rows = table
+        .children
+        .borrow()
+        .iter()
+        .filter(|child| is_tag(child, "thead"))
+        .map(|thead| {
+            thead
+                .children
+                .borrow()
+                .iter()
+                .filter(|row| is_tag(row, "tr"))
+                .count()
+        })
+        .sum::<usize>();
+    if header_rows > 1 {
+        found.push(TableLoss::MultipleHeaderRows);
+    }
+    let order = [
+        TableLoss::ColSpan,
+        TableLoss::RowSpan,
+        TableLoss::NestedTable,
+        TableLoss::BlockContent,
+        TableLoss::MultipleHeaderRows,
+    ];
+    order
+        .into_iter()
+        .filter(|loss| found.contains(loss))
+        .collect()
+}
+
+fn scan(handle: &Handle, found: &mut Vec<TableLoss>, in_cell: bool, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    for child in handle.children.borrow().iter() {
+        let NodeData::Element { ref name, .. } = child.data else {
+            continue;
+        };
+        let tag = name.local.as_ref();
+        match tag {
+            "td" | "th" if !in_cell => {
+                let span = |attr| {
+                    get_attr(child, attr)
+                        .and_then(|v| v.trim().parse::<u32>().ok())
+                        .is_some_and(|n| n > 1)
+                };
+                if span("colspan") {
+                    found.push(TableLoss::ColSpan);
+                }
+                if span("rowspan") {
+                    found.push(TableLoss::RowSpan);
+                }
+                let paragraphs = child
+                    .children
+                    .borrow()
+                    .iter()
+                    .filter(|c| is_tag(c, "p") || is_tag(c, "div"))
+                    .count();
+                if paragraphs > 1 {
+                    found.push(TableLoss::BlockContent);
+                }
+                scan(child, found, true, depth + 1);
+            }
+            "table" if in_cell => found.push(TableLoss::NestedTable),
+            "ul" | "ol" | "dl" | "pre" | "listing" | "xmp" | "blockquote" | "hr" | "h1" | "h2"
+            | "h3" | "h4" | "h5" | "h6"
+                if in_cell =>
+            {
+                found.push(TableLoss::BlockContent)
+            }
+            _ => scan(child, found, in_cell, depth + 1),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Layout tables
+// ---------------------------------------------------------------------------
+
+/// Whether `table` is used for page layout rather than data: it has
+/// `role="presentation"` (or `none`), or it has no header (`th`, `thead`,
+/// `caption`) and is a single column, holds block content, or nests other
+/// tables.
+pub(crate) fn is_layout(table: &Handle) -> bool {
+    let role = get_attr(table, "role").map(|role| role.trim().to_ascii_lowercase());
+    if matches!(role.as_deref(), Some("presentation" | "none")) {
+        return true;
+    }
+    let rows = rows(table);
+    let has_header = table
+        .children
+        .borrow()
+        .iter()
+        .any(|child| is_tag(child, "thead") || is_tag(child, "caption"))
+        || rows
+            .iter()
+            .any(|row| cells(row).iter().any(|cell| is_tag(cell, "th")));
+    if rows.is_empty() || has_header {
+        return false;
+    }
+    let single_column = rows.iter().all(|row| cells(row).len() <= 1);
+    single_column
+        || losses(table)
+            .iter()
+            .any(|loss| matches!(loss, TableLoss::BlockContent | TableLoss::NestedTable))
+}
+
+// ---------------------------------------------------------------------------
+// Header promotion
+// ---------------------------------------------------------------------------
+
+/// Whether the first row of a headless `table` looks like a header: every
+/// non-empty cell is `scope="col"` or entirely bold.
+pub(crate) fn has_header_row(table: &Handle) -> bool {
+    let Some(row) = rows(table).into_iter().next() else {
+        return false;
+    };
+    let mut found = false;
+    for cell in &cells(&row) {
+        if get_attr(cell, "scope").is_some_and(|scope| scope.eq_ignore_ascii_case("col")) {
+            found = true;
+            continue;
+        }
+        let mut bold = false;
+        if !all_text_bold(cell, false, &mut bold, 0) {
+            return false;
+        }
+        found |= bold;
+    }
+    found
+}
+
+/// The `tr` rows of `table` itself, including those in `thead`/`tbody`/
+/// `tfoot`, in document order.
+pub(crate) fn rows(table: &Handle) -> Vec<Handle> {
+    let mut rows = Vec::new();
+    for child in table.children.borrow().iter() {
+        if is_tag(child, "tr") {
+            rows.push(child.clone());
+        } else if is_tag(child, "thead") || is_tag(child, "tbody") || is_tag(child, "tfoot") {
+            rows.extend(
+                child
+                    .children
+                    .borrow()
+                    .iter()
+                    .filter(|row| is_tag(row, "tr"))
+                    .cloned(),
+            );
+        }
+    }
+    rows
+}
+
+/// The `td`/`th` cells of `row`.
+pub(crate) fn cells(row: &Handle) -> Vec<Handle> {
+    row.children
+        .borrow()
+        .iter()
+        .filter(|cell| is_tag(cell, "td") || is_tag(cell, "th"))
+        .cloned()
+        .collect()
+}
+
+/// Whether all non-whitespace text under `handle` is inside `<b>` or
+/// `<strong>`; `found` is set when there is any.
+fn all_text_bold(handle: &Handle, in_bold: bool, found: &mut bool, depth: usize) -> bool {
+    if depth >= MAX_DEPTH {
+        return true;
+    }
+    match handle.data {
+        NodeData::Text { ref contents } => {
+            if contents.borrow().trim().is_empty() {
+                return true;
+            }
+            *found |= in_bold;
+            in_bold
+        }
+        NodeData::Element { ref name, .. } => {
+            let in_bold = in_bold || matches!(name.local.as_ref(), "b" | "strong");
+            handle
+                .children
+                .borrow()
+                .iter()
+                .all(|child| all_text_bold(child, in_bold, found, depth + 1))
+        }
+        _ => true,
+    }
+}
+
+/// Replace `Strong` nodes with their children.
+pub(crate) fn unbold(nodes: Vec<Node>) -> Vec<Node> {
+    nodes
+        .into_iter()
+        .flat_map(|node| match node {
+            Node::Strong(strong) => unbold(strong.children),
+            other => vec![other],
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// HTML output
+// ---------------------------------------------------------------------------
+
+/// Attributes kept in fallback HTML; the rest are presentational.
+const KEPT_ATTRIBUTES: &[&str] = &["colspan", "rowspan", "href", "src", "alt", "title", "scope"];
+
+/// Elements written on their own line, to keep the block readable.
+const ROW_LEVEL: &[&str] = &["table", "caption", "thead", "tbody", "tfoot", "tr"];
+
+/// Elements dropped with their contents.
+const DROPPED: &[&str] = &["script", "style", "template", "noscript", "colgroup", "col"];
+
+/// Elements kept in fallback HTML: table structure, phrasing markup and
+/// `<pre>`. Others are unwrapped to their content.
+const KEPT_TAGS: &[&str] = &[
+    "table", "caption", "thead", "tbody", "tfoot", "tr", "th", "td", "pre", "a", "abbr", "b",
+    "bdi", "bdo", "br", "cite", "code", "del", "dfn", "em", "i", "img", "ins", "kbd", "mark", "q",
+    "s", "samp", "small", "span", "strong", "sub", "sup", "time", "u", "var", "wbr",
+];
+
+/// Unwrapped elements whose content is set apart from what precedes it.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "li",
+    "nav",
+    "ol",
+    "p",
+    "section",
+    "ul",
+];
+
+/// URL schemes never written to fallback HTML.
+const UNSAFE_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:"];
+
+/// Serialize `table` as a compact HTML block of table and phrasing tags.
+/// Blank lines (which would end the HTML block in Markdown) never appear in
+/// the output.
+pub(crate) fn to_html(state: &State, table: &Handle) -> String {
+    let mut html = String::new();
+    write(state, table, &mut html, false, 0);
+    html.trim_end().to_string()
+}
+
+fn write(state: &State, handle: &Handle, html: &mut String, in_pre: bool, depth: usize) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    match handle.data {
+        NodeData::Text { ref contents } => {
+            let text = contents.borrow();
+            if in_pre {
+                // `&#10;` keeps blank or whitespace-only lines, including
+                // ones split across elements, from ending the HTML block.
+                let escaped = escape(&text, false);
+                html.push_str(&escaped.replace('\n', "&#10;"));
+                return;
+            }
+            let collapsed = collapse(&text);
+            // Indentation between rows and cells.
+            if collapsed == " " && html.ends_with('\n') {
+                return;
+            }
+            html.push_str(&escape(&collapsed, false));
+        }
+        NodeData::Element {
+            ref name,
+            ref attrs,
+            ..
+        } => {
+            let tag = name.local.as_ref();
+            if DROPPED.contains(&tag) || is_skipped(state, handle) {
+                return;
+            }
+            if !KEPT_TAGS.contains(&tag) {
+                if BLOCKS.contains(&tag) && !at_content_start(html) {
+                    html.push(' ');
+                }
+                for child in handle.children.borrow().iter() {
+                    write(state, child, html, in_pre, depth + 1);
+                }
+                return;
+            }
+            let row_level = ROW_LEVEL.contains(&tag) && tag != "caption";
+            if (row_level || tag == "caption") && !html.is_empty() && !html.ends_with('\n') {
+                html.push('\n');
+            }
+            html.push('<');
+            html.push_str(tag);
+            for attr in attrs.borrow().iter() {
+                let attr_name = attr.name.local.as_ref();
+                if !KEPT_ATTRIBUTES.contains(&attr_name) {
+                    continue;
+                }
+                let value = match attr_name {
+                    "href" | "src" if is_unsafe_url(&attr.value) => continue,
+                    "href" | "src" => state.resolve(&attr.value),
+                    _ => attr.value.to_string(),
+                };
+                html.push_str(&format!(" {attr_name}=\"{}\"", escape(&value, true)));
+            }
+            html.push('>');
+            if is_void(tag) {
+                return;
+            }
+            if row_level {
+                html.push('\n');
+            }
+            let in_pre = in_pre || tag == "pre";
+            for child in handle.children.borrow().iter() {
+                write(state, child, html, in_pre, depth + 1);
+            }
+            if row_level && !html.ends_with('\n') {
+                html.push('\n');
+            }
+            html.push_str(&format!("</{tag}>"));
+            if ROW_LEVEL.contains(&tag) || tag == "td" || tag == "th" {
+                html.push('\n');
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Elements the Markdown conversion would skip as well.
+fn is_skipped(state: &State, handle: &Handle) -> bool {
+    get_attr(handle, "data-mdast").as_deref() == Some("ignore")
+        || state
+            .options
+            .exclude_selectors
+            .iter()
+            .any(|selector| selector.matches(handle))
+}
+
+/// Whether `html` ends at the start of a cell or caption, or after
+/// whitespace, where unwrapped content needs no separator.
+fn at_content_start(html: &str) -> bool {
+    if html.is_empty() || html.ends_with(char::is_whitespace) {
+        return true;
+    }
+    html.ends_with('>')
+        && html.rfind('<').is_some_and(|open| {
+            let tag = &html[open..];
+            ["<td", "<th", "<caption"].iter().any(|cell| {
+                tag.strip_prefix(cell)
+                    .is_some_and(|rest| rest.starts_with(['>', ' ']))
+            })
+        })
+}
+
+/// Whether `url` uses a scheme that runs script or embeds content.
+fn is_unsafe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    UNSAFE_SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+}
+
+fn is_void(tag: &str) -> bool {
+    matches!(tag, "br" | "img" | "hr" | "wbr" | "input" | "col")
+}
+
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !space {
+                out.push(' ');
+            }
+            space = true;
+        } else {
+            out.push(c);
+            space = false;
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Cell lines
+// ---------------------------------------------------------------------------
+
+/// Flatten cell content to phrasing: one line per hard break, paragraph,
+/// heading, list item and code line, separated by `Break`s. List items start
+/// with `• ` (or their number when ordered).
+pub(crate) fn cell_lines(children: Vec<Node>) -> Vec<Node> {
+    let mut lines = vec![Vec::new()];
+    push_lines(children, &mut lines);
+    let mut result = Vec::new();
+    for line in finish(lines) {
+        if !result.is_empty() {
+            result.push(Node::Break(mdast::Break));
+        }
+        result.extend(line);
+    }
+    result
+}
+
+fn push_lines(nodes: Vec<Node>, lines: &mut Vec<Vec<Node>>) {
+    for mut node in nodes {
+        match node {
+            Node::Break(_) | Node::ThematicBreak(_) => lines.push(Vec::new()),
+            Node::List(list) => {
+                let mut number = list.start.unwrap_or(1);
+                for item in list.children {
+                    let Node::ListItem(item) = item else {
+                        continue;
+                    };
+                    let mut item_lines = vec![Vec::new()];
+                    push_lines(item.children, &mut item_lines);
+                    let mut item_lines = finish(item_lines);
+                    let marker = if list.ordered {
+                        format!("{number}. ")
+                    } else {
+                        "• ".to_string()
+                    };
+                    number += 1;
+                    if let Some(first) = item_lines.first_mut() {
+                        first.insert(0, Node::Text(mdast::Text { value: marker }));
+                    }
+                    lines.append(&mut item_lines);
+                    lines.push(Vec::new());
+                }
+            }
+            Node::Code(code) => {
+                for line in code.value.lines() {
+                    lines.push(vec![Node::InlineCode(mdast::InlineCode {
+                        value: line.to_string(),
+                    })]);
+                }
+                lines.push(Vec::new());
+            }
+            _ if node.is_phrasing() => {
+                if let Some(line) = lines.last_mut() {
+                    line.push(node);
+                }
+            }
+            _ => match node.children_mut() {
+                // Paragraphs, headings, block quotes, …: their own lines.
+                Some(children) => {
+                    let children = std::mem::take(children);
+                    lines.push(Vec::new());
+                    push_lines(children, lines);
+                    lines.push(Vec::new());
+                }
+                None => {
+                    if let Some(line) = lines.last_mut() {
+                        line.push(node);
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Trim the edges of each line and drop blank ones.
+fn finish(lines: Vec<Vec<Node>>) -> Vec<Vec<Node>> {
+    lines
+        .into_iter()
+        .filter_map(|mut line| {
+            if let Some(Node::Text(text)) = line.first_mut() {
+                text.value = text.value.trim_start().to_string();
+            }
+            if let Some(Node::Text(text)) = line.last_mut() {
+                text.value = text.value.trim_end().to_string();
+            }
+            line.retain(|node| !matches!(node, Node::Text(t) if t.value.is_empty()));
+            (!is_whitespace_only(&line)).then_some(line)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapse_keeps_edge_spaces() {
+        assert_eq!(collapse(" a \n\t b "), " a b ");
+    }
+
+    fn text(value: &str) -> Node {
+        Node::Text(mdast::Text {
+            value: value.to_string(),
+        })
+    }
+
+    fn paragraph(value: &str) -> Node {
+        Node::Paragraph(mdast::Paragraph {
+            children: vec![text(value)],
+        })
+    }
+
+    #[test]
+    fn cell_lines_separate_blocks() {
+        let br = Node::Break(mdast::Break);
+        let lines = cell_lines(vec![
+            text(" a"),
+            Node::Break(mdast::Break),
+            text("b "),
+            paragraph("c"),
+            Node::List(mdast::List {
+                ordered: true,
+                start: Some(3),
+                spread: false,
+                children: vec![Node::ListItem(mdast::ListItem {
+                    spread: false,
+                    checked: None,
+                    children: vec![paragraph(" d")],
+                })],
+            }),
+        ]);
+        assert_eq!(
+            lines,
+            vec![
+                text("a"),
+                br.clone(),
+                text("b"),
+                br.clone(),
+                text("c"),
+                br,
+                text("3. "),
+                text("d"),
+            ]
+        );
+    }
+}
@@ -27,6 +27,27 @@ pub(crate) fn drop_surrounding_breaks(mut nodes: Vec<mdast::Node>) -> Vec<mdast:
     nodes
 }
 
+/// Collapse runs of whitespace to one space and trim the ends, for short
+/// labels (titles, captions, attribute values).
+pub(crate) fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Escape text for HTML; quotes too when `attribute`.
+pub(crate) fn escape(text: &str, attribute: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 /// Check if a list of nodes contains only whitespace-only text.
 pub(crate) fn is_whitespace_only(nodes: &[mdast::Node]) -> bool {
     nodes.iter().all(|n| match n {
@@ -34,3 +55,14 @@ pub(crate) fn is_whitespace_only(nodes: &[mdast::Node]) -> bool {
         _ => false,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_text_and_attributes() {
+        assert_eq!(escape("a<b & \"c\"", false), "a&lt;b &amp; \"c\"");
+        assert_eq!(escape("\"", true), "&quot;");
+    }
+}
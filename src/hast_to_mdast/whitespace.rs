@@ -28,7 +28,8 @@ fn post_process_whitespace_inner(node: &mut Node, depth: usize) {
         children.retain(|child| !is_empty_text(child));
 
         // Normalize inline element boundaries: deduplicate spaces at
-        // Link/Delete edges and trim their leading/trailing whitespace.
+        // Link/Delete (and extension) edges and trim their leading/trailing
+        // whitespace.
         // This mirrors rehype-minify-whitespace's inline whitespace handling:
         // when an inline element's last text ends with a space and the
         // following text starts with a space, the space stays inside the
@@ -41,7 +42,11 @@ fn post_process_whitespace_inner(node: &mut Node, depth: usize) {
     // in delete content at block boundaries (mirrors rehype-minify-whitespace).
     let should_trim = matches!(
         node,
-        Node::Heading(_) | Node::Paragraph(_) | Node::Root(_) | Node::Delete(_)
+        Node::Heading(_)
+            | Node::Paragraph(_)
+            | Node::Root(_)
+            | Node::Delete(_)
+            | Node::DefinitionTerm(_)
     );
     if should_trim {
         if let Some(children) = node.children_mut() {
@@ -113,9 +118,17 @@ fn normalize_inline_boundaries(children: &mut Vec<Node>) {
     children.retain(|child| !is_empty_text(child));
 }
 
-/// Return true if `node` is a Link or Delete.
+/// Return true if `node` is a Link, Delete, or an inline extension node.
 fn is_link_or_delete(node: &Node) -> bool {
-    matches!(node, Node::Link(_) | Node::Delete(_))
+    matches!(
+        node,
+        Node::Link(_)
+            | Node::Delete(_)
+            | Node::Highlight(_)
+            | Node::Superscript(_)
+            | Node::Subscript(_)
+            | Node::Insert(_)
+    )
 }
 
 /// Return true if the last text descendant of an inline node ends with ' '.
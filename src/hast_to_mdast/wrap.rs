@@ -151,6 +151,7 @@ fn wrap_parent_inside_child(template: &WrapperTemplate, child: Node) -> Node {
             Node::Heading(mdast::Heading {
                 depth: h.depth,
                 children: vec![inner],
+                attributes: h.attributes,
             })
         }
         Node::Paragraph(p) => {
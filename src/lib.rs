@@ -25,11 +25,29 @@
 //! - [`justext`](https://crates.io/crates/justext) — paragraph-level boilerplate
 //!   removal using stopword density.
 
+use std::collections::HashMap;
+
+mod converter;
 mod hast_to_mdast;
 pub mod mdast;
 mod stringify;
 
-pub use stringify::{HeadingStyle, ListItemIndent, StringifyOptions};
+pub use converter::{Converter, Serializer};
+pub use hast_to_mdast::custom::{Element, ElementHandler, TransformContext};
+pub use hast_to_mdast::guess::{guess_language, LanguageGuess};
+pub use hast_to_mdast::metadata::Metadata;
+pub use hast_to_mdast::references::{DefinitionPlacement, LinkStyle};
+pub use hast_to_mdast::select::{Selector, SelectorError};
+pub use hast_to_mdast::structured::StructuredData;
+pub use hast_to_mdast::table_data::TableData;
+pub use hast_to_mdast::tables::{TableCaption, TableLoss, TableStrategy};
+pub use hast_to_mdast::KeepAttributes;
+/// The `serde_json` version whose `Value` [`StructuredData`] holds.
+pub use serde_json;
+pub use stringify::custom::{NodeHandler, StringifyContext};
+pub use stringify::{
+    AdmonitionStyle, HeadingStyle, InlineSyntax, ListItemIndent, StringifyOptions, TableStyle,
+};
 
 /// Conversion options.
 #[derive(Debug, Clone)]
@@ -46,6 +64,117 @@ pub struct Options {
     /// Each entry is 1 or 2 chars: open (and optionally close).
     /// Default: `['"']` (plain ASCII double-quote for both open and close).
     pub quotes: Vec<String>,
+    /// Convert only the outermost elements matching this selector, in
+    /// document order, instead of the whole document. When nothing matches,
+    /// the output is empty. Default: `None`.
+    pub root_selector: Option<Selector>,
+    /// Skip elements matching any of these selectors, together with their
+    /// contents, as if they carried `data-mdast="ignore"`. Default: empty.
+    pub exclude_selectors: Vec<Selector>,
+    /// Convert only the page's main content, chosen by Readability-style
+    /// scoring of text density, link density and class/id hints. Falls back
+    /// to the whole `<body>` when no candidate stands out. Ignored when
+    /// [`Options::root_selector`] is set. Default: `false`.
+    pub main_content: bool,
+    /// Emit a leading YAML frontmatter block built from the page's
+    /// [`Metadata`] (title, description, author, date, canonical URL, …).
+    /// Default: `false`.
+    pub frontmatter: bool,
+    /// Structured-data properties merged into the frontmatter, as
+    /// `(key, property path)` pairs resolved with [`StructuredData::find`].
+    /// A key that matches a metadata field replaces it. Only used when
+    /// [`Options::frontmatter`] is set. Default: empty.
+    pub frontmatter_properties: Vec<(String, String)>,
+    /// Write links and images inline (`[text](url)`) or as references
+    /// (`[text][1]` plus `[1]: url`). Identical targets share one numbered
+    /// definition. Default: [`LinkStyle::Inline`].
+    pub link_style: LinkStyle,
+    /// Where reference definitions are placed when [`Options::link_style`] is
+    /// [`LinkStyle::Reference`]. Default: [`DefinitionPlacement::EndOfDocument`].
+    pub definition_placement: DefinitionPlacement,
+    /// Keep heading `id`s (and optionally classes) as `{#id .class}`
+    /// attribute blocks so in-page `#anchor` links keep working.
+    /// Default: [`KeepAttributes::None`].
+    pub keep_attributes: KeepAttributes,
+    /// Rewrite in-page `#id` links to the GitHub-style slug of the heading
+    /// they point at (`#install-1` for the second "Install" heading). Links
+    /// to other elements point at the nearest preceding heading; headings
+    /// that keep their id via [`Options::keep_attributes`] are linked by it.
+    /// Default: `false`.
+    pub rewrite_fragment_links: bool,
+    /// Code block language aliases, e.g. `js` → `javascript`, applied to
+    /// the language found on `<code>`, `<pre>` or their wrappers. Keys are
+    /// lowercase; mapping to `""` drops the language (e.g. `nohighlight`).
+    /// See [`Options::with_default_language_aliases`] for a built-in set.
+    /// Default: empty.
+    pub language_aliases: HashMap<String, String>,
+    /// Guess the language of code blocks that have no language markup from
+    /// their text (see [`guess_language`]), and use the guess when its
+    /// confidence is at least this value (0–1). Default: `None` (off).
+    pub language_guess_threshold: Option<f32>,
+    /// Remove the indentation shared by every line of a code block (common
+    /// when the HTML source was indented along with its `<pre>`).
+    /// Default: `false`.
+    pub code_dedent: bool,
+    /// Leave line-number gutters (Pygments `linenos`, highlight.js
+    /// `hljs-ln`, Rouge `rouge-gutter`, Prism `line-numbers-rows`, or a
+    /// digits-only cell of a one-row table around a `<pre>`) and
+    /// copy-to-clipboard buttons out of code blocks. Default: `false`.
+    pub code_strip_chrome: bool,
+    /// When a code block starts with a shell (`$ `, `% `) or Python REPL
+    /// (`>>> `) prompt, remove the prompts (and `> `/`... ` continuations)
+    /// and record the prompt in the block's meta as `prompt="$"`, so the
+    /// block can be copied and run. Blocks without a language are labelled
+    /// `console` (shell) or `pycon` (Python), since meta needs a language.
+    /// Default: `false`.
+    pub code_strip_prompts: bool,
+    /// Fill the fence meta string of code blocks (```` ```rust title="main.rs" {3-5} ````)
+    /// from [`Options::code_meta_attributes`] and from a caption next to the
+    /// code (`<figcaption>`, file-name headers), which is then not repeated
+    /// as text. Code blocks without a language get no meta. Default: `false`.
+    pub code_meta: bool,
+    /// `(attribute, template)` pairs used when [`Options::code_meta`] is set.
+    /// Attributes are read from the `<pre>`, its `<code>`, and their
+    /// wrappers; `{}` in the template is replaced by the value. Default:
+    /// `data-title`/`data-filename`/`data-file` → `title="{}"`,
+    /// `data-line` → `{{}}`.
+    pub code_meta_attributes: Vec<(String, String)>,
+    /// Write every table as a GFM table, or keep tables GFM cannot represent
+    /// (spans, nested tables, block content in cells, several header rows)
+    /// as cleaned-up HTML. See [`table_losses`]. Default: [`TableStrategy::Gfm`].
+    pub table_strategy: TableStrategy,
+    /// Repeat a `colspan`/`rowspan` cell's content in every slot it covers,
+    /// both in Markdown tables and in [`extract_tables`]. Otherwise the
+    /// covered slots are empty. Default: `false`.
+    pub table_duplicate_spans: bool,
+    /// Write a table's `<caption>` as a paragraph before it, plain or
+    /// emphasized. Default: [`TableCaption::Drop`].
+    pub table_caption: TableCaption,
+    /// In tables without a header, use the first row as the header when it
+    /// looks like one (every non-empty cell bold or `scope="col"`) instead
+    /// of adding an empty header row. Default: `false`.
+    pub table_promote_header: bool,
+    /// Unwrap tables that look like page layout rather than data into
+    /// ordinary content: `role="presentation"`, or no header cells and a
+    /// single column, block content in cells, or nested tables. Common in
+    /// old CMS and email HTML. Default: `false`.
+    pub unwrap_layout_tables: bool,
+    /// Elements written as raw HTML around their converted content instead
+    /// of being flattened or approximated: any of `abbr`, `details`, `del`,
+    /// `ins`, `kbd`, `mark`, `s`, `samp`, `small`, `sub`, `sup`, `time`, `u`
+    /// or `var`, matched case-insensitively. Other names are ignored. Only
+    /// `title`, `open` and `datetime` attributes are kept. Default: empty.
+    pub keep_html: Vec<String>,
+    /// `(class, kind)` pairs recognizing admonitions when
+    /// [`StringifyOptions::admonition_style`] is set, checked in order;
+    /// `role="note"` is always a note. Classes without a `-` or `_` (`note`,
+    /// `warning`, …) only count on an `<aside>`, an element with
+    /// `role="note"` or `role="alert"`, or one with an `admonition*`,
+    /// `alert-*` or `callout-*` class. Default: the classes of Sphinx,
+    /// MkDocs, Docusaurus, Bootstrap and Confluence (`warning`,
+    /// `alert-warning`, `confluence-information-macro-warning`, …) mapped to
+    /// GitHub's `note`, `tip`, `important`, `warning` and `caution`.
+    pub admonition_kinds: Vec<(String, String)>,
 }
 
 impl Default for Options {
@@ -56,6 +185,35 @@ impl Default for Options {
             checked: None,
             unchecked: None,
             quotes: vec!["\"".to_string()],
+            root_selector: None,
+            exclude_selectors: Vec::new(),
+            main_content: false,
+            frontmatter: false,
+            frontmatter_properties: Vec::new(),
+            link_style: LinkStyle::Inline,
+            definition_placement: DefinitionPlacement::EndOfDocument,
+            keep_attributes: KeepAttributes::None,
+            rewrite_fragment_links: false,
+            language_aliases: HashMap::new(),
+            language_guess_threshold: None,
+            code_dedent: false,
+            code_strip_chrome: false,
+            code_strip_prompts: false,
+            code_meta: false,
+            code_meta_attributes: hast_to_mdast::code::DEFAULT_META_ATTRIBUTES
+                .iter()
+                .map(|&(attr, template)| (attr.to_string(), template.to_string()))
+                .collect(),
+            table_strategy: TableStrategy::Gfm,
+            table_duplicate_spans: false,
+            table_caption: TableCaption::Drop,
+            table_promote_header: false,
+            unwrap_layout_tables: false,
+            keep_html: Vec::new(),
+            admonition_kinds: hast_to_mdast::admonitions::DEFAULT_KINDS
+                .iter()
+                .map(|&(class, kind)| (class.to_string(), kind.to_string()))
+                .collect(),
         }
     }
 }
@@ -220,11 +378,296 @@ impl Options {
         self
     }
 
+    /// Set how tables are written.
+    pub fn with_table_style(mut self, style: TableStyle) -> Self {
+        self.stringify.table_style = style;
+        self
+    }
+
+    /// Set how `<mark>` is written: `==mark==`, raw HTML, or emphasis.
+    ///
+    /// Also changes the MDAST tree: anything but [`InlineSyntax::Fallback`]
+    /// makes the converter produce `Highlight` nodes, so set it on the options
+    /// given to [`Converter`] even when serializing separately.
+    pub fn with_highlight(mut self, syntax: InlineSyntax) -> Self {
+        self.stringify.highlight = syntax;
+        self
+    }
+
+    /// Set how `<sup>` is written: `^sup^`, raw HTML, or plain text.
+    ///
+    /// Also changes the MDAST tree: anything but [`InlineSyntax::Fallback`]
+    /// makes the converter produce `Superscript` nodes, so set it on the options
+    /// given to [`Converter`] even when serializing separately.
+    pub fn with_superscript(mut self, syntax: InlineSyntax) -> Self {
+        self.stringify.superscript = syntax;
+        self
+    }
+
+    /// Set how `<sub>` is written: `~sub~`, raw HTML, or plain text.
+    ///
+    /// Also changes the MDAST tree: anything but [`InlineSyntax::Fallback`]
+    /// makes the converter produce `Subscript` nodes, so set it on the options
+    /// given to [`Converter`] even when serializing separately.
+    pub fn with_subscript(mut self, syntax: InlineSyntax) -> Self {
+        self.stringify.subscript = syntax;
+        self
+    }
+
+    /// Set how `<ins>` and `<u>` are written: `++ins++`, raw HTML, or the
+    /// usual plain text (`<ins>`) and emphasis (`<u>`).
+    ///
+    /// Also changes the MDAST tree: anything but [`InlineSyntax::Fallback`]
+    /// makes the converter produce `Insert` nodes, so set it on the options
+    /// given to [`Converter`] even when serializing separately.
+    pub fn with_insert(mut self, syntax: InlineSyntax) -> Self {
+        self.stringify.insert = syntax;
+        self
+    }
+
+    /// Set whether to convert math (MathML, KaTeX, MathJax and `\(...\)`)
+    /// to `$…$` and `$$` blocks. Otherwise math is dropped.
+    pub fn with_math(mut self, math: bool) -> Self {
+        self.stringify.math = math;
+        self
+    }
+
+    /// Set whether to write `<dl>` as a definition list (PHP Markdown Extra
+    /// and Pandoc syntax: the term, then `: definition` on the next line;
+    /// terms sharing definitions each repeat them).
+    /// Otherwise terms and definitions become a bulleted list, which any
+    /// CommonMark parser reads.
+    pub fn with_definition_lists(mut self, definition_lists: bool) -> Self {
+        self.stringify.definition_lists = definition_lists;
+        self
+    }
+
+    /// Set how notes, tips and warnings are written: GitHub alerts,
+    /// `:::` directives, or Obsidian callouts. Otherwise they are not
+    /// recognized.
+    ///
+    /// Also changes the MDAST tree: anything but [`AdmonitionStyle::Off`]
+    /// makes the converter produce `Admonition` nodes, so set it on the
+    /// options given to [`Converter`] even when serializing separately.
+    pub fn with_admonition_style(mut self, style: AdmonitionStyle) -> Self {
+        self.stringify.admonition_style = style;
+        self
+    }
+
     /// Set whether to preserve newlines in whitespace normalization.
     pub fn with_newlines(mut self, newlines: bool) -> Self {
         self.newlines = newlines;
         self
     }
+
+    /// Set how links and images are written.
+    pub fn with_link_style(mut self, style: LinkStyle) -> Self {
+        self.link_style = style;
+        self
+    }
+
+    /// Set where reference definitions are placed.
+    pub fn with_definition_placement(mut self, placement: DefinitionPlacement) -> Self {
+        self.definition_placement = placement;
+        self
+    }
+
+    /// Set which element attributes are kept as `{#id .class}` blocks.
+    pub fn with_keep_attributes(mut self, keep: KeepAttributes) -> Self {
+        self.keep_attributes = keep;
+        self
+    }
+
+    /// Set whether in-page `#id` links are rewritten to heading slugs.
+    pub fn with_rewrite_fragment_links(mut self, rewrite: bool) -> Self {
+        self.rewrite_fragment_links = rewrite;
+        self
+    }
+
+    /// Write code blocks labelled `alias` (case-insensitive) as `language`,
+    /// or without a language if `language` is empty. May be called
+    /// repeatedly.
+    pub fn with_language_alias(mut self, alias: &str, language: &str) -> Self {
+        self.language_aliases
+            .insert(alias.to_lowercase(), language.to_string());
+        self
+    }
+
+    /// Add the built-in aliases: common short names mapped to full ones
+    /// (`js` → `javascript`, `sh` and `shell` → `bash`, `py` → `python`,
+    /// `yml` → `yaml`, …) and `nohighlight` to no language. Aliases already
+    /// set are kept, and later [`Options::with_language_alias`] calls
+    /// override these.
+    pub fn with_default_language_aliases(mut self) -> Self {
+        for &(alias, language) in hast_to_mdast::code::DEFAULT_LANGUAGE_ALIASES {
+            self.language_aliases
+                .entry(alias.to_string())
+                .or_insert_with(|| language.to_string());
+        }
+        self
+    }
+
+    /// Guess the language of unlabelled code blocks, keeping guesses with at
+    /// least `threshold` confidence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is not between 0 and 1.
+    pub fn with_language_guessing(mut self, threshold: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&threshold),
+            "language guess threshold must be between 0 and 1, got {threshold}"
+        );
+        self.language_guess_threshold = Some(threshold);
+        self
+    }
+
+    /// Set whether to remove indentation shared by all lines of code blocks.
+    pub fn with_code_dedent(mut self, dedent: bool) -> Self {
+        self.code_dedent = dedent;
+        self
+    }
+
+    /// Set whether to leave line-number gutters and copy buttons out of
+    /// code blocks.
+    pub fn with_code_strip_chrome(mut self, strip: bool) -> Self {
+        self.code_strip_chrome = strip;
+        self
+    }
+
+    /// Set whether to move leading shell/REPL prompts into code block meta,
+    /// labelling blocks without a language `console` or `pycon`.
+    pub fn with_code_strip_prompts(mut self, strip: bool) -> Self {
+        self.code_strip_prompts = strip;
+        self
+    }
+
+    /// Set whether to fill code block meta from attributes and captions.
+    pub fn with_code_meta(mut self, code_meta: bool) -> Self {
+        self.code_meta = code_meta;
+        self
+    }
+
+    /// Map the code attribute `attr` to a meta `template` (e.g.
+    /// `("data-highlight", "hl_lines=\"{}\"")`), replacing any existing
+    /// mapping for `attr`. Only used when [`Options::code_meta`] is set.
+    pub fn with_code_meta_attribute(mut self, attr: &str, template: &str) -> Self {
+        self.code_meta_attributes.retain(|(a, _)| a != attr);
+        self.code_meta_attributes
+            .push((attr.to_string(), template.to_string()));
+        self
+    }
+
+    /// Set how tables that GFM cannot represent are converted.
+    pub fn with_table_strategy(mut self, strategy: TableStrategy) -> Self {
+        self.table_strategy = strategy;
+        self
+    }
+
+    /// Set whether to keep line structure in table cells with `<br>`.
+    pub fn with_table_cell_breaks(mut self, breaks: bool) -> Self {
+        self.stringify.table_cell_breaks = breaks;
+        self
+    }
+
+    /// Set whether spanning cells repeat their content in covered slots.
+    pub fn with_table_duplicate_spans(mut self, duplicate: bool) -> Self {
+        self.table_duplicate_spans = duplicate;
+        self
+    }
+
+    /// Set whether and how table captions are written.
+    pub fn with_table_caption(mut self, caption: TableCaption) -> Self {
+        self.table_caption = caption;
+        self
+    }
+
+    /// Set whether a bold first row becomes the header of a headless table.
+    pub fn with_table_promote_header(mut self, promote: bool) -> Self {
+        self.table_promote_header = promote;
+        self
+    }
+
+    /// Set whether layout tables are unwrapped into ordinary content.
+    pub fn with_unwrap_layout_tables(mut self, unwrap: bool) -> Self {
+        self.unwrap_layout_tables = unwrap;
+        self
+    }
+
+    /// Keep elements with this tag name as raw HTML.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` is not one of the elements listed on
+    /// [`Options::keep_html`].
+    pub fn with_keep_html(mut self, tag: &str) -> Self {
+        let tag = tag.to_ascii_lowercase();
+        assert!(
+            hast_to_mdast::handlers::KEEPABLE_HTML.contains(&tag.as_str()),
+            "cannot keep <{tag}> as HTML"
+        );
+        if !self.keep_html.contains(&tag) {
+            self.keep_html.push(tag);
+        }
+        self
+    }
+
+    /// Recognize elements with `class` as admonitions of `kind` (e.g.
+    /// `("panel-danger", "caution")`), replacing any existing mapping for
+    /// `class`. Checked before the built-in mappings.
+    pub fn with_admonition_kind(mut self, class: &str, kind: &str) -> Self {
+        self.admonition_kinds.retain(|(c, _)| c != class);
+        self.admonition_kinds
+            .insert(0, (class.to_string(), kind.to_ascii_lowercase()));
+        self
+    }
+
+    /// Set whether to convert only the main content of the page.
+    pub fn with_main_content(mut self, main_content: bool) -> Self {
+        self.main_content = main_content;
+        self
+    }
+
+    /// Set whether to emit YAML frontmatter from the page metadata.
+    pub fn with_frontmatter(mut self, frontmatter: bool) -> Self {
+        self.frontmatter = frontmatter;
+        self
+    }
+
+    /// Merge the structured-data property at `path` (e.g. `"author.name"`)
+    /// into the frontmatter under `key`. May be called repeatedly.
+    pub fn with_frontmatter_property(mut self, key: &str, path: &str) -> Self {
+        self.frontmatter_properties
+            .push((key.to_string(), path.to_string()));
+        self
+    }
+
+    /// Convert only the subtrees matching `selector` (e.g. `"article .post-body"`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `selector` is not a valid selector; use [`Selector::parse`]
+    /// and set [`Options::root_selector`] directly to handle the error.
+    pub fn with_root_selector(mut self, selector: &str) -> Self {
+        self.root_selector = Some(parse_selector(selector));
+        self
+    }
+
+    /// Skip elements matching `selector` (e.g. `".share-buttons, nav"`).
+    /// May be called repeatedly; an element matching any selector is skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `selector` is not a valid selector; use [`Selector::parse`]
+    /// and push onto [`Options::exclude_selectors`] directly to handle the error.
+    pub fn with_exclude_selector(mut self, selector: &str) -> Self {
+        self.exclude_selectors.push(parse_selector(selector));
+        self
+    }
+}
+
+fn parse_selector(selector: &str) -> Selector {
+    Selector::parse(selector).unwrap_or_else(|err| panic!("{err}"))
 }
 
 /// Convert an HTML string to Markdown using default options.
@@ -255,20 +698,149 @@ pub fn convert_with(html: &str, options: &Options) -> String {
     mdast_to_string(&mdast, &options.stringify)
 }
 
-/// Parse HTML and transform it into an MDAST tree.
+/// Check each table in `html` (outermost tables, in document order) for
+/// structure a GFM table would lose. An empty list means the table converts
+/// losslessly; with [`TableStrategy::HtmlFallback`], tables with a non-empty
+/// list are kept as HTML. Like [`extract_tables`], only tables that
+/// `options` would convert are checked (see `root_selector`,
+/// `main_content` and `exclude_selectors`).
+///
+/// ```
+/// use html2markdown::{table_losses, Options, TableLoss};
+///
+/// let html = r#"<table><tr><td colspan="2">a</td></tr></table>
+///               <table><tr><td>b</td></tr></table>"#;
+/// assert_eq!(
+///     table_losses(html, &Options::new()),
+///     vec![vec![TableLoss::ColSpan], vec![]]
+/// );
+/// ```
+pub fn table_losses(html: &str, options: &Options) -> Vec<Vec<TableLoss>> {
+    hast_to_mdast::table_losses(
+        html,
+        transform_options(options),
+        &hast_to_mdast::custom::ElementHandlers::default(),
+    )
+}
+
+/// Every outermost table in `html` as a grid of plain-text cells, in
+/// document order. Only the part of the page that would be converted is
+/// searched (see `root_selector`, `main_content` and `exclude_selectors`).
+///
+/// ```
+/// use html2markdown::{extract_tables, Options};
+///
+/// let html = r#"<table><caption>Stock</caption>
+///               <tr><th>Item</th><th align="right">Qty</th></tr>
+///               <tr><td>Apple</td><td>3</td></tr></table>"#;
+/// let tables = extract_tables(html, &Options::new());
+/// assert_eq!(tables[0].caption.as_deref(), Some("Stock"));
+/// assert_eq!(tables[0].header, Some(vec!["Item".to_string(), "Qty".to_string()]));
+/// assert_eq!(tables[0].rows, vec![vec!["Apple".to_string(), "3".to_string()]]);
+/// ```
+pub fn extract_tables(html: &str, options: &Options) -> Vec<TableData> {
+    hast_to_mdast::extract_tables(
+        html,
+        transform_options(options),
+        &hast_to_mdast::custom::ElementHandlers::default(),
+    )
+}
+
+/// Parse HTML and transform it into an MDAST tree. Page metadata and
+/// structured data are not gathered; see [`html_to_document`].
 pub fn html_to_mdast(html: &str, options: &Options) -> mdast::Node {
-    let transform_options = hast_to_mdast::TransformOptions {
+    hast_to_mdast::transform(
+        html,
+        transform_options(options),
+        &hast_to_mdast::custom::ElementHandlers::default(),
+        false,
+    )
+    .tree
+}
+
+/// A converted document: the MDAST tree plus page-level data gathered from
+/// the same parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    /// The MDAST tree, as returned by [`html_to_mdast`].
+    pub tree: mdast::Node,
+    /// Metadata from `<title>`, `<meta>`, `<link rel=canonical>` and `lang`.
+    pub metadata: Metadata,
+    /// JSON-LD blocks and microdata items.
+    pub structured_data: StructuredData,
+}
+
+/// Parse HTML and transform it into an MDAST tree, returning the page
+/// metadata and structured data alongside it.
+///
+/// # Examples
+///
+/// ```
+/// use html2markdown::{html_to_document, Options};
+///
+/// let html = r#"<html lang="en"><head><title>Hello</title></head><body><p>Hi</p></body></html>"#;
+/// let doc = html_to_document(html, &Options::new());
+/// assert_eq!(doc.metadata.title.as_deref(), Some("Hello"));
+/// assert_eq!(doc.metadata.lang.as_deref(), Some("en"));
+/// ```
+pub fn html_to_document(html: &str, options: &Options) -> Document {
+    hast_to_mdast::transform(
+        html,
+        transform_options(options),
+        &hast_to_mdast::custom::ElementHandlers::default(),
+        true,
+    )
+}
+
+/// Extract the transformer's options from the public conversion options.
+pub(crate) fn transform_options(options: &Options) -> hast_to_mdast::TransformOptions {
+    hast_to_mdast::TransformOptions {
         newlines: options.newlines,
         checked: options.checked.clone(),
         unchecked: options.unchecked.clone(),
         quotes: options.quotes.clone(),
-    };
-    hast_to_mdast::transform(html, transform_options)
+        root_selector: options.root_selector.clone(),
+        exclude_selectors: options.exclude_selectors.clone(),
+        main_content: options.main_content,
+        frontmatter: options.frontmatter,
+        frontmatter_properties: options.frontmatter_properties.clone(),
+        link_style: options.link_style,
+        definition_placement: options.definition_placement,
+        keep_attributes: options.keep_attributes,
+        rewrite_fragment_links: options.rewrite_fragment_links,
+        language_aliases: options.language_aliases.clone(),
+        language_guess_threshold: options.language_guess_threshold,
+        code_dedent: options.code_dedent,
+        code_strip_chrome: options.code_strip_chrome,
+        code_strip_prompts: options.code_strip_prompts,
+        code_meta: options.code_meta,
+        code_meta_attributes: options.code_meta_attributes.clone(),
+        table_strategy: options.table_strategy,
+        table_cell_breaks: options.stringify.table_cell_breaks,
+        table_duplicate_spans: options.table_duplicate_spans,
+        table_caption: options.table_caption,
+        table_promote_header: options.table_promote_header,
+        unwrap_layout_tables: options.unwrap_layout_tables,
+        keep_html: options
+            .keep_html
+            .iter()
+            .map(|tag| tag.to_ascii_lowercase())
+            .filter(|tag| hast_to_mdast::handlers::KEEPABLE_HTML.contains(&tag.as_str()))
+            .collect(),
+        highlight: options.stringify.highlight != InlineSyntax::Fallback,
+        superscript: options.stringify.superscript != InlineSyntax::Fallback,
+        subscript: options.stringify.subscript != InlineSyntax::Fallback,
+        insert: options.stringify.insert != InlineSyntax::Fallback,
+        math: options.stringify.math,
+        definition_lists: options.stringify.definition_lists,
+        admonitions: options.stringify.admonition_style != AdmonitionStyle::Off,
+        admonition_kinds: options.admonition_kinds.clone(),
+    }
 }
 
 /// Serialize an MDAST tree to a Markdown string.
 pub fn mdast_to_string(node: &mdast::Node, options: &StringifyOptions) -> String {
-    stringify::stringify(node, options)
+    stringify::stringify(node, options, &stringify::custom::NodeHandlers::default())
 }
 
 #[cfg(test)]
@@ -54,6 +54,17 @@ pub struct Blockquote {
     pub children: Vec<Node>,
 }
 
+/// Admonition: a note, tip or warning set apart from the text
+/// (`> [!WARNING]`, `:::warning`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Admonition {
+    /// Lowercase kind, e.g. `note` or `warning`.
+    pub kind: String,
+    /// Title, if it says more than the kind.
+    pub title: Option<String>,
+    pub children: Vec<Node>,
+}
+
 /// Fenced or indented code block.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Code {
@@ -67,6 +78,23 @@ pub struct Code {
 pub struct Heading {
     pub depth: u8, // 1–6
     pub children: Vec<Node>,
+    /// Written as a trailing `{#id .class}` block when not empty.
+    pub attributes: Attributes,
+}
+
+/// Attribute block (`{#id .class}`) as used by Pandoc, kramdown and
+/// markdown-it-attrs. Not part of mdast proper.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Attributes {
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+}
+
+impl Attributes {
+    /// Whether there is nothing to write.
+    pub fn is_empty(&self) -> bool {
+        self.id.is_none() && self.classes.is_empty()
+    }
 }
 
 /// Raw HTML.
@@ -135,6 +163,12 @@ pub struct InlineCode {
     pub value: String,
 }
 
+/// Raw HTML inside a paragraph (`<sup>`, `</kbd>`), written as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineHtml {
+    pub value: String,
+}
+
 /// Hard line break (`\` or two spaces at end of line).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Break;
@@ -181,6 +215,66 @@ pub struct Delete {
     pub children: Vec<Node>,
 }
 
+// Definition lists ---------------------------------------------------------
+
+/// Definition list (`<dl>`): each term followed by its descriptions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionList {
+    pub children: Vec<Node>,
+}
+
+/// Term in a definition list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionTerm {
+    pub children: Vec<Node>,
+}
+
+/// Description of a term (`: definition`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionDescription {
+    pub children: Vec<Node>,
+}
+
+// Math ---------------------------------------------------------------------
+
+/// Display math (`$$` block).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Math {
+    pub value: String,
+}
+
+/// Inline math (`$…$`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineMath {
+    pub value: String,
+}
+
+// Inline extensions --------------------------------------------------------
+
+/// Highlighted text (`==text==`, `<mark>`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    pub children: Vec<Node>,
+}
+
+/// Superscript (`^text^`, `<sup>`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Superscript {
+    pub children: Vec<Node>,
+}
+
+/// Subscript (`~text~`, `<sub>`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscript {
+    pub children: Vec<Node>,
+}
+
+/// Inserted (underlined) text (`++text++`, `<ins>`, `<u>`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insert {
+    pub children: Vec<Node>,
+}
+
 /// GFM table.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Table {
@@ -249,6 +343,7 @@ pub enum Node {
     Root(Root),
 
     // Flow (block) content
+    Admonition(Admonition),
     Blockquote(Blockquote),
     Code(Code),
     Heading(Heading),
@@ -266,11 +361,27 @@ pub enum Node {
     Image(Image),
     ImageReference(ImageReference),
     InlineCode(InlineCode),
+    InlineHtml(InlineHtml),
     Link(Link),
     LinkReference(LinkReference),
     Strong(Strong),
     Text(Text),
 
+    // Definition lists
+    DefinitionList(DefinitionList),
+    DefinitionTerm(DefinitionTerm),
+    DefinitionDescription(DefinitionDescription),
+
+    // Math
+    Math(Math),
+    InlineMath(InlineMath),
+
+    // Inline extensions
+    Highlight(Highlight),
+    Superscript(Superscript),
+    Subscript(Subscript),
+    Insert(Insert),
+
     // Table (GFM)
     Table(Table),
     TableRow(TableRow),
@@ -285,10 +396,54 @@ pub enum Node {
 }
 
 impl Node {
+    /// The mdast `type` of this node (e.g. `"paragraph"`, `"inlineCode"`).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Node::Root(_) => "root",
+            Node::Admonition(_) => "admonition",
+            Node::Blockquote(_) => "blockquote",
+            Node::Code(_) => "code",
+            Node::Heading(_) => "heading",
+            Node::Html(_) => "html",
+            Node::List(_) => "list",
+            Node::ListItem(_) => "listItem",
+            Node::ThematicBreak(_) => "thematicBreak",
+            Node::Definition(_) => "definition",
+            Node::Paragraph(_) => "paragraph",
+            Node::Break(_) => "break",
+            Node::Delete(_) => "delete",
+            Node::Emphasis(_) => "emphasis",
+            Node::Image(_) => "image",
+            Node::ImageReference(_) => "imageReference",
+            Node::InlineCode(_) => "inlineCode",
+            Node::InlineHtml(_) => "inlineHtml",
+            Node::Link(_) => "link",
+            Node::LinkReference(_) => "linkReference",
+            Node::Strong(_) => "strong",
+            Node::Text(_) => "text",
+            Node::DefinitionList(_) => "definitionList",
+            Node::DefinitionTerm(_) => "definitionTerm",
+            Node::DefinitionDescription(_) => "definitionDescription",
+            Node::Math(_) => "math",
+            Node::InlineMath(_) => "inlineMath",
+            Node::Highlight(_) => "highlight",
+            Node::Superscript(_) => "superscript",
+            Node::Subscript(_) => "subscript",
+            Node::Insert(_) => "insert",
+            Node::Table(_) => "table",
+            Node::TableRow(_) => "tableRow",
+            Node::TableCell(_) => "tableCell",
+            Node::FootnoteDefinition(_) => "footnoteDefinition",
+            Node::FootnoteReference(_) => "footnoteReference",
+            Node::Yaml(_) => "yaml",
+        }
+    }
+
     /// Returns a reference to this node's children, if it has any.
     pub fn children(&self) -> Option<&[Node]> {
         match self {
             Node::Root(n) => Some(&n.children),
+            Node::Admonition(n) => Some(&n.children),
             Node::Blockquote(n) => Some(&n.children),
             Node::Heading(n) => Some(&n.children),
             Node::List(n) => Some(&n.children),
@@ -299,6 +454,13 @@ impl Node {
             Node::Delete(n) => Some(&n.children),
             Node::Link(n) => Some(&n.children),
             Node::LinkReference(n) => Some(&n.children),
+            Node::DefinitionList(n) => Some(&n.children),
+            Node::DefinitionTerm(n) => Some(&n.children),
+            Node::DefinitionDescription(n) => Some(&n.children),
+            Node::Highlight(n) => Some(&n.children),
+            Node::Superscript(n) => Some(&n.children),
+            Node::Subscript(n) => Some(&n.children),
+            Node::Insert(n) => Some(&n.children),
             Node::Table(n) => Some(&n.children),
             Node::TableRow(n) => Some(&n.children),
             Node::TableCell(n) => Some(&n.children),
@@ -311,6 +473,7 @@ impl Node {
     pub fn children_mut(&mut self) -> Option<&mut Vec<Node>> {
         match self {
             Node::Root(n) => Some(&mut n.children),
+            Node::Admonition(n) => Some(&mut n.children),
             Node::Blockquote(n) => Some(&mut n.children),
             Node::Heading(n) => Some(&mut n.children),
             Node::List(n) => Some(&mut n.children),
@@ -321,6 +484,13 @@ impl Node {
             Node::Delete(n) => Some(&mut n.children),
             Node::Link(n) => Some(&mut n.children),
             Node::LinkReference(n) => Some(&mut n.children),
+            Node::DefinitionList(n) => Some(&mut n.children),
+            Node::DefinitionTerm(n) => Some(&mut n.children),
+            Node::DefinitionDescription(n) => Some(&mut n.children),
+            Node::Highlight(n) => Some(&mut n.children),
+            Node::Superscript(n) => Some(&mut n.children),
+            Node::Subscript(n) => Some(&mut n.children),
+            Node::Insert(n) => Some(&mut n.children),
             Node::Table(n) => Some(&mut n.children),
             Node::TableRow(n) => Some(&mut n.children),
             Node::TableCell(n) => Some(&mut n.children),
@@ -333,7 +503,8 @@ impl Node {
     ///
     /// Note: `Html` is flow content (block-level), not phrasing. HTML comments
     /// and raw HTML between block elements should be treated as block nodes.
-    /// This matches mdast-util-phrasing behavior.
+    /// This matches mdast-util-phrasing behavior. Raw HTML within a
+    /// paragraph is `InlineHtml`.
     pub fn is_phrasing(&self) -> bool {
         matches!(
             self,
@@ -343,23 +514,31 @@ impl Node {
                 | Node::Image(_)
                 | Node::ImageReference(_)
                 | Node::InlineCode(_)
+                | Node::InlineHtml(_)
                 | Node::Link(_)
                 | Node::LinkReference(_)
                 | Node::Strong(_)
                 | Node::Text(_)
                 | Node::FootnoteReference(_)
+                | Node::InlineMath(_)
+                | Node::Highlight(_)
+                | Node::Superscript(_)
+                | Node::Subscript(_)
+                | Node::Insert(_)
         )
     }
 
     /// Whether this node is flow (block) content.
     ///
-    /// Note: `ListItem`, `TableRow`, `TableCell`, and `Yaml` return `false`
-    /// here; they belong to list-content and frontmatter content models
-    /// respectively in the mdast spec.
+    /// Note: `ListItem`, `TableRow`, `TableCell`, `DefinitionTerm`,
+    /// `DefinitionDescription`, and `Yaml` return `false` here; they belong
+    /// to list-content, table-content, definition-list content and
+    /// frontmatter content models respectively.
     pub fn is_flow(&self) -> bool {
         matches!(
             self,
-            Node::Blockquote(_)
+            Node::Admonition(_)
+                | Node::Blockquote(_)
                 | Node::Code(_)
                 | Node::Heading(_)
                 | Node::Html(_)
@@ -369,6 +548,8 @@ impl Node {
                 | Node::Paragraph(_)
                 | Node::Table(_)
                 | Node::FootnoteDefinition(_)
+                | Node::Math(_)
+                | Node::DefinitionList(_)
         )
     }
 }
@@ -424,6 +605,16 @@ mod tests {
         assert!(node.children().is_none());
     }
 
+    #[test]
+    fn test_type_name_uses_mdast_names() {
+        let node = Node::InlineCode(InlineCode { value: "x".into() });
+        assert_eq!(node.type_name(), "inlineCode");
+        assert_eq!(
+            Node::ThematicBreak(ThematicBreak).type_name(),
+            "thematicBreak"
+        );
+    }
+
     #[test]
     fn test_root_default() {
         let root = Root::default();
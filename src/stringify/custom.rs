@@ -0,0 +1,151 @@
+// Custom node handlers.
+//
+// Lets callers replace how particular MDAST nodes are written without
+// forking `handlers::handle`. Handlers are looked up before the built-in
+// dispatch, keyed by mdast node type (`"image"`, `"link"`, …) or a predicate.
+
+use std::sync::Arc;
+
+use super::{State, StringifyOptions};
+use crate::mdast::{self, Node};
+
+/// A user-supplied serialization for MDAST nodes.
+///
+/// Implemented for any `Fn(&mut StringifyContext, &Node) -> String` closure.
+///
+/// ```rust
+/// use html2markdown::mdast::Node;
+/// use html2markdown::{Options, Serializer, StringifyContext, StringifyOptions};
+///
+/// let serializer = Serializer::new(StringifyOptions::default()).with_handler(
+///     "image",
+///     |_: &mut StringifyContext, node: &Node| match node {
+///         Node::Image(image) => format!("{{{{< figure src=\"{}\" >}}}}", image.url),
+///         _ => unreachable!(),
+///     },
+/// );
+/// let tree = html2markdown::html_to_mdast("<p><img src=\"a.png\"></p>", &Options::new());
+/// assert_eq!(serializer.stringify(&tree), "{{< figure src=\"a.png\" >}}\n");
+/// ```
+///
+/// A `"listItem"` handler returns the item's content: the list still writes
+/// the marker and indents continuation lines. Likewise a `"tableCell"`
+/// handler returns the cell's content, which the table pads (text passed
+/// through [`StringifyContext::escape`] has its `|` escaped there).
+/// `"tableRow"` handlers are never consulted, since rows are laid out by
+/// the table.
+pub trait NodeHandler: Send + Sync {
+    /// Serialize `node` to Markdown.
+    fn handle(&self, cx: &mut StringifyContext, node: &Node) -> String;
+}
+
+impl<F> NodeHandler for F
+where
+    F: Fn(&mut StringifyContext, &Node) -> String + Send + Sync,
+{
+    fn handle(&self, cx: &mut StringifyContext, node: &Node) -> String {
+        self(cx, node)
+    }
+}
+
+/// Access to the serializer from inside a [`NodeHandler`].
+///
+/// Going through the context keeps serializer state (escaping context, list
+/// bullet alternation, table-cell pipes) consistent with the built-in output.
+pub struct StringifyContext<'s, 'a> {
+    pub(crate) state: &'s mut State<'a>,
+}
+
+impl StringifyContext<'_, '_> {
+    /// The serializer options in effect.
+    pub fn options(&self) -> &StringifyOptions {
+        self.state.options
+    }
+
+    /// Serialize `node`, consulting registered handlers first.
+    ///
+    /// Calling this on the node currently being handled re-enters the same
+    /// handler; use [`StringifyContext::default`] for that instead.
+    pub fn handle(&mut self, node: &Node) -> String {
+        super::handlers::handle(self.state, node)
+    }
+
+    /// Serialize `node` with the built-in handler, ignoring custom ones.
+    pub fn default(&mut self, node: &Node) -> String {
+        super::handlers::handle_default(self.state, node)
+    }
+
+    /// Serialize inline children flush together.
+    pub fn container_phrasing(&mut self, children: &[Node]) -> String {
+        super::phrasing::container_phrasing(self.state, children)
+    }
+
+    /// Serialize block children separated by blank lines.
+    pub fn container_flow(&mut self, children: &[Node]) -> String {
+        super::flow::container_flow(self.state, children)
+    }
+
+    /// Escape `text` exactly as a text node in the current position would be.
+    pub fn escape(&mut self, text: &str) -> String {
+        let node = mdast::Text {
+            value: text.to_string(),
+        };
+        super::handlers::handle_text(self.state, &node)
+    }
+}
+
+type Predicate = dyn Fn(&Node) -> bool + Send + Sync;
+
+/// Which nodes a registered handler applies to.
+enum Matcher {
+    Type(String),
+    Predicate(Box<Predicate>),
+}
+
+impl Matcher {
+    fn matches(&self, node: &Node) -> bool {
+        match self {
+            Matcher::Type(name) => node.type_name() == name,
+            Matcher::Predicate(predicate) => predicate(node),
+        }
+    }
+}
+
+/// A registered handler and the nodes it applies to.
+pub(crate) struct Entry {
+    matcher: Matcher,
+    pub handler: Box<dyn NodeHandler>,
+}
+
+/// Registered custom handlers, consulted before the built-in dispatch.
+#[derive(Clone, Default)]
+pub(crate) struct NodeHandlers {
+    entries: Vec<Arc<Entry>>,
+}
+
+impl NodeHandlers {
+    /// Register a handler for an mdast node type.
+    pub fn insert_type(&mut self, type_name: &str, handler: Box<dyn NodeHandler>) {
+        let matcher = Matcher::Type(type_name.to_string());
+        self.entries.push(Arc::new(Entry { matcher, handler }));
+    }
+
+    /// Register a handler for nodes matching `predicate`.
+    pub fn insert_predicate(&mut self, predicate: Box<Predicate>, handler: Box<dyn NodeHandler>) {
+        let matcher = Matcher::Predicate(predicate);
+        self.entries.push(Arc::new(Entry { matcher, handler }));
+    }
+
+    /// Find the handler for `node`. Later registrations take precedence.
+    pub fn find(&self, node: &Node) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.matcher.matches(node))
+            .map(|entry| entry.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
@@ -9,6 +9,8 @@ use std::sync::LazyLock;
 
 use regex::Regex;
 
+use super::{InlineSyntax, StringifyOptions};
+
 /// Escape special Markdown characters in phrasing (inline) text content.
 ///
 /// In phrasing context, these characters can trigger Markdown constructs:
@@ -200,6 +202,64 @@ pub(crate) fn escape_at_break_start(mut content: String) -> String {
     content
 }
 
+/// Escape the delimiters of inline extensions written as extension syntax:
+/// every `^` (superscript), `~` (subscript) and `$` (math), and `==`
+/// (highlight) and `++` (insert) pairs. Runs on already-escaped text, so
+/// characters that are already backslash-escaped are left alone.
+pub(crate) fn escape_extensions<'a>(
+    text: Cow<'a, str>,
+    options: &StringifyOptions,
+) -> Cow<'a, str> {
+    let enabled = |syntax: InlineSyntax| syntax == InlineSyntax::Extension;
+    let (sup, sub) = (enabled(options.superscript), enabled(options.subscript));
+    let (mark, ins) = (enabled(options.highlight), enabled(options.insert));
+    let math = options.math;
+    if !(sup || sub || mark || ins || math) {
+        return text;
+    }
+    let bytes = text.as_bytes();
+    let mut result = String::with_capacity(text.len() + 8);
+    let mut last = 0;
+    let mut backslashes = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let next = bytes.get(i + 1).copied();
+        let delimiter = match b {
+            b'^' => sup,
+            b'~' => sub,
+            b'=' => mark && next == Some(b'='),
+            b'+' => ins && next == Some(b'+'),
+            b'$' => math,
+            _ => false,
+        };
+        // Same ASCII-only byte slicing as `escape_phrasing`.
+        if delimiter && backslashes % 2 == 0 {
+            result.push_str(&text[last..i]);
+            result.push('\\');
+            last = i;
+        }
+        backslashes = if b == b'\\' { backslashes + 1 } else { 0 };
+    }
+    if last == 0 && result.is_empty() {
+        return text;
+    }
+    result.push_str(&text[last..]);
+    Cow::Owned(result)
+}
+
+/// Escape every `ch` not already backslash-escaped, e.g. `$` in TeX.
+pub(crate) fn escape_unescaped(text: &str, ch: char) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut backslashes = 0;
+    for c in text.chars() {
+        if c == ch && backslashes % 2 == 0 {
+            result.push('\\');
+        }
+        backslashes = if c == '\\' { backslashes + 1 } else { 0 };
+        result.push(c);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +283,32 @@ mod tests {
         assert_eq!(escape_phrasing("~foo~"), "~foo~"); // single tildes: no escape
         assert_eq!(escape_phrasing("~/.bashrc"), "~/.bashrc"); // single tilde: no escape
     }
+
+    #[test]
+    fn escape_extensions_only_when_enabled() {
+        let text = "2^10 a==b c++ ~x \\^";
+        let plain = StringifyOptions::default();
+        assert_eq!(escape_extensions(Cow::Borrowed(text), &plain), text);
+        let all = StringifyOptions {
+            highlight: InlineSyntax::Extension,
+            superscript: InlineSyntax::Extension,
+            subscript: InlineSyntax::Extension,
+            insert: InlineSyntax::Extension,
+            ..StringifyOptions::default()
+        };
+        assert_eq!(
+            escape_extensions(Cow::Borrowed(text), &all),
+            "2\\^10 a\\==b c\\++ \\~x \\^"
+        );
+        let html = StringifyOptions {
+            superscript: InlineSyntax::Html,
+            ..StringifyOptions::default()
+        };
+        assert_eq!(escape_extensions(Cow::Borrowed("2^10"), &html), "2^10");
+    }
+
+    #[test]
+    fn escape_unescaped_skips_escaped() {
+        assert_eq!(escape_unescaped("a$b\\$c\\\\$", '$'), "a\\$b\\$c\\\\\\$");
+    }
 }
@@ -4,16 +4,29 @@
 
 use std::borrow::Cow;
 
-use super::State;
+use super::custom::StringifyContext;
+use super::{AdmonitionStyle, InlineSyntax, State, TableStyle};
 use crate::mdast::{self, Node};
 
 /// Dispatch to the appropriate handler for a node.
+/// Custom handlers take precedence over the built-in ones.
 pub(crate) fn handle(state: &mut State, node: &Node) -> String {
+    let handlers = state.handlers;
+    if let Some(entry) = handlers.find(node) {
+        let mut cx = StringifyContext { state };
+        return entry.handler.handle(&mut cx, node);
+    }
+    handle_default(state, node)
+}
+
+/// Dispatch to the built-in handler for a node.
+pub(crate) fn handle_default(state: &mut State, node: &Node) -> String {
     match node {
         Node::Root(n) => handle_root(state, n),
         Node::Paragraph(n) => handle_paragraph(state, n),
         Node::Heading(n) => handle_heading(state, n),
         Node::ThematicBreak(_) => handle_thematic_break(state),
+        Node::Admonition(n) => handle_admonition(state, n),
         Node::Blockquote(n) => handle_blockquote(state, n),
         Node::List(n) => handle_list(state, n),
         Node::ListItem(n) => handle_list_item(state, n),
@@ -24,17 +37,43 @@ pub(crate) fn handle(state: &mut State, node: &Node) -> String {
         Node::Emphasis(n) => handle_emphasis(state, n),
         Node::Strong(n) => handle_strong(state, n),
         Node::InlineCode(n) => handle_inline_code(n),
+        Node::InlineHtml(n) => n.value.clone(),
         Node::Break(_) => handle_break(),
         Node::Link(n) => handle_link(state, n),
         Node::Image(n) => handle_image(n),
         Node::LinkReference(n) => handle_link_reference(state, n),
         Node::ImageReference(n) => handle_image_reference(n),
         Node::Delete(n) => handle_delete(state, n),
-        Node::Table(n) => handle_table(state, n),
-        Node::TableRow(_) | Node::TableCell(_) => {
-            // Handled by table handler directly.
-            String::new()
+        Node::DefinitionList(n) => handle_definition_list(state, n),
+        Node::DefinitionTerm(n) => handle_definition_term(state, n),
+        Node::DefinitionDescription(n) => handle_definition_description(state, n),
+        Node::Math(n) => format!("$$\n{}\n$$", n.value),
+        Node::InlineMath(n) => handle_inline_math(state, n),
+        Node::Highlight(n) if state.options.highlight == InlineSyntax::Fallback => {
+            let emphasis = mdast::Emphasis {
+                children: n.children.clone(),
+            };
+            handle_emphasis(state, &emphasis)
         }
+        Node::Highlight(n) => {
+            handle_extension(state, state.options.highlight, "==", "mark", &n.children)
+        }
+        // A footnote mark in `<sup>` is a footnote reference already, and
+        // `^[` would start a Pandoc inline note.
+        Node::Superscript(n) if is_footnote_mark(&n.children) => {
+            super::phrasing::container_phrasing(state, &n.children)
+        }
+        Node::Superscript(n) => {
+            handle_extension(state, state.options.superscript, "^", "sup", &n.children)
+        }
+        Node::Subscript(n) => {
+            handle_extension(state, state.options.subscript, "~", "sub", &n.children)
+        }
+        Node::Insert(n) => handle_extension(state, state.options.insert, "++", "ins", &n.children),
+        Node::Table(n) => handle_table(state, n),
+        // Rows are laid out by the table handler.
+        Node::TableRow(_) => String::new(),
+        Node::TableCell(n) => super::phrasing::container_phrasing(state, &n.children),
         Node::FootnoteDefinition(n) => handle_footnote_definition(state, n),
         Node::FootnoteReference(n) => handle_footnote_reference(n),
         Node::Yaml(n) => handle_yaml(n),
@@ -57,7 +96,19 @@ fn handle_paragraph(state: &mut State, node: &mdast::Paragraph) -> String {
 }
 
 fn handle_heading(state: &mut State, node: &mdast::Heading) -> String {
-    let content = super::phrasing::container_phrasing(state, &node.children);
+    let mut content = super::phrasing::container_phrasing(state, &node.children);
+    // Attributes follow the closing sequence of a closed ATX heading, where
+    // extended syntaxes look for them; elsewhere they end the content.
+    let mut attributes = (!node.attributes.is_empty()).then(|| format_attributes(&node.attributes));
+    let trailing = state.options.close_atx && !content.trim().is_empty();
+    if !trailing {
+        if let Some(attributes) = attributes.take() {
+            if !content.is_empty() {
+                content.push(' ');
+            }
+            content.push_str(&attributes);
+        }
+    }
 
     // Use setext for h1/h2 if: (a) setext style is configured, or (b) content
     // contains a newline (from Break nodes or text with preserved newlines).
@@ -67,6 +118,10 @@ fn handle_heading(state: &mut State, node: &mdast::Heading) -> String {
             || content.contains('\n'));
 
     if use_setext {
+        if let Some(attributes) = attributes {
+            content.push(' ');
+            content.push_str(&attributes);
+        }
         let marker = if node.depth == 1 { '=' } else { '-' };
         let line_len = content
             .lines()
@@ -94,7 +149,10 @@ fn handle_heading(state: &mut State, node: &mdast::Heading) -> String {
 
     let hashes = "#".repeat(node.depth as usize);
     if state.options.close_atx {
-        format!("{} {} {}", hashes, content, hashes)
+        match attributes {
+            Some(attributes) => format!("{} {} {} {}", hashes, content, hashes, attributes),
+            None => format!("{} {} {}", hashes, content, hashes),
+        }
     } else {
         format!("{} {}", hashes, content)
     }
@@ -156,9 +214,103 @@ fn handle_blockquote(state: &mut State, node: &mdast::Blockquote) -> String {
         .join("\n")
 }
 
+/// GitHub kinds of alert.
+const ALERT_KINDS: &[&str] = &["note", "tip", "important", "warning", "caution"];
+
+fn handle_admonition(state: &mut State, node: &mdast::Admonition) -> String {
+    let style = match state.options.admonition_style {
+        AdmonitionStyle::Alert if state.in_alert => AdmonitionStyle::Off,
+        style => style,
+    };
+    let old_in_alert = state.in_alert;
+    state.in_alert = style == AdmonitionStyle::Alert;
+    let content = super::flow::container_flow(state, &node.children);
+    state.in_alert = old_in_alert;
+    let title = node.title.as_deref();
+    let (marker, content) = match style {
+        AdmonitionStyle::Directive => {
+            // Fence longer than any directive fence nested inside.
+            let nested = content
+                .lines()
+                .map(|line| line.chars().take_while(|&c| c == ':').count())
+                .max()
+                .unwrap_or(0);
+            let fence = ":".repeat((nested + 1).max(3));
+            let label = title
+                .map(|title| format!("[{}]", title.replace('[', "\\[").replace(']', "\\]")))
+                .unwrap_or_default();
+            let mut result = format!("{fence}{}{label}\n", node.kind);
+            if !content.is_empty() {
+                result.push_str(&content);
+                result.push('\n');
+            }
+            result.push_str(&fence);
+            return result;
+        }
+        AdmonitionStyle::Callout => {
+            let marker = match title {
+                Some(title) => {
+                    let title = handle_text(
+                        state,
+                        &mdast::Text {
+                            value: title.to_string(),
+                        },
+                    );
+                    format!("[!{}] {title}", node.kind)
+                }
+                None => format!("[!{}]", node.kind),
+            };
+            (Some(marker), content)
+        }
+        // GitHub alerts have no title: it leads the content in bold.
+        AdmonitionStyle::Alert => {
+            let kind = if ALERT_KINDS.contains(&node.kind.as_str()) {
+                node.kind.as_str()
+            } else {
+                "note"
+            };
+            let marker = format!("[!{}]", kind.to_ascii_uppercase());
+            (Some(marker), titled(state, title, content))
+        }
+        AdmonitionStyle::Off => (None, titled(state, title, content)),
+    };
+    marker
+        .into_iter()
+        .chain(content.lines().map(String::from))
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `content` preceded by a bold `title` paragraph, if any.
+fn titled(state: &mut State, title: Option<&str>, content: String) -> String {
+    let Some(title) = title else {
+        return content;
+    };
+    let strong = mdast::Strong {
+        children: vec![Node::Text(mdast::Text {
+            value: title.to_string(),
+        })],
+    };
+    let title = handle_strong(state, &strong);
+    if content.is_empty() {
+        title
+    } else {
+        format!("{title}\n\n{content}")
+    }
+}
+
 fn handle_list(state: &mut State, node: &mdast::List) -> String {
     let mut result = Vec::new();
     let old_bullet = state.bullet_current;
+    let old_in_spread_list = state.in_spread_list;
+    state.in_spread_list = node.spread;
 
     // For unordered lists: alternate bullets when sibling list used the same bullet.
     // For ordered lists: alternate `.` / `)` delimiters when the previous sibling
@@ -203,7 +355,7 @@ fn handle_list(state: &mut State, node: &mdast::List) -> String {
             format!("{}", state.bullet_current.unwrap_or('*'))
         };
 
-        let content = handle_list_item_with_parent(state, child, node);
+        let content = handle(state, child);
         // Reset bullet_last_used after each list item to prevent state from
         // nested lists in one item leaking into sibling items' nested lists.
         state.bullet_last_used = None;
@@ -274,43 +426,17 @@ fn handle_list(state: &mut State, node: &mdast::List) -> String {
         state.bullet_last_used = state.bullet_current;
     }
     state.bullet_current = old_bullet;
+    state.in_spread_list = old_in_spread_list;
 
     let separator = if node.spread { "\n\n" } else { "\n" };
     result.join(separator)
 }
 
-/// Render a list item, respecting whether the parent list is spread.
-fn handle_list_item_with_parent(state: &mut State, node: &Node, parent: &mdast::List) -> String {
-    let spread = parent.spread
-        || if let Node::ListItem(li) = node {
-            li.spread
-        } else {
-            false
-        };
-
-    let content = if let Node::ListItem(li) = node {
-        let mut content = super::flow::container_flow_tight(state, &li.children, spread);
-
-        if let Some(checked) = li.checked {
-            let checkbox = if checked { "[x]" } else { "[ ]" };
-            if content.is_empty() {
-                content = checkbox.to_string();
-            } else {
-                content = format!("{} {}", checkbox, content);
-            }
-        }
-        content
-    } else {
-        handle(state, node)
-    };
-
-    content
-}
-
+/// A list item's content, without its marker and indentation (added by
+/// `handle_list`). An item is spread if it or its parent list is.
 fn handle_list_item(state: &mut State, node: &mdast::ListItem) -> String {
-    // This is called directly (not via handle_list), so we don't know spread.
-    // Default to the node's own spread setting.
-    let mut content = super::flow::container_flow_tight(state, &node.children, node.spread);
+    let spread = node.spread || state.in_spread_list;
+    let mut content = super::flow::container_flow_tight(state, &node.children, spread);
 
     if let Some(checked) = node.checked {
         let checkbox = if checked { "[x]" } else { "[ ]" };
@@ -324,8 +450,87 @@ fn handle_list_item(state: &mut State, node: &mdast::ListItem) -> String {
     content
 }
 
+/// Definition list (PHP Markdown Extra, Pandoc): each term on its own line,
+/// followed by its descriptions. Pandoc has no syntax for several terms
+/// sharing descriptions, so they are repeated under each term. A blank line
+/// separates entries, and descriptions of more than one block.
+fn handle_definition_list(state: &mut State, node: &mdast::DefinitionList) -> String {
+    let blocks = |node: &Node| match node {
+        Node::DefinitionDescription(description) => description.children.len(),
+        _ => 1,
+    };
+    // Group terms with the descriptions that follow them.
+    let mut groups: Vec<(Vec<&Node>, Vec<&Node>)> = Vec::new();
+    for child in &node.children {
+        match groups.last_mut() {
+            Some((_, descriptions)) if matches!(child, Node::DefinitionDescription(_)) => {
+                descriptions.push(child);
+            }
+            Some((terms, descriptions))
+                if descriptions.is_empty() && matches!(child, Node::DefinitionTerm(_)) =>
+            {
+                terms.push(child);
+            }
+            _ => groups.push((vec![child], Vec::new())),
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (terms, descriptions) in groups {
+        let mut written = String::new();
+        let mut previous: Option<&Node> = None;
+        for &description in &descriptions {
+            if let Some(previous) = previous {
+                let tight = blocks(previous) <= 1 && blocks(description) <= 1;
+                written.push_str(if tight { "\n" } else { "\n\n" });
+            }
+            written.push_str(&handle(state, description));
+            previous = Some(description);
+        }
+        for term in terms {
+            let mut entry = handle(state, term);
+            if !written.is_empty() {
+                entry.push('\n');
+                entry.push_str(&written);
+            }
+            entries.push(entry);
+        }
+    }
+    entries.join("\n\n")
+}
+
+/// Term: one line of phrasing.
+fn handle_definition_term(state: &mut State, node: &mdast::DefinitionTerm) -> String {
+    state.at_break = true;
+    let content = super::phrasing::container_phrasing(state, &node.children);
+    state.at_break = false;
+    content.replace("\\\n", " ").replace('\n', " ")
+}
+
+/// Description: `: ` then its content, continuation lines indented four
+/// spaces.
+fn handle_definition_description(state: &mut State, node: &mdast::DefinitionDescription) -> String {
+    let content = super::flow::container_flow(state, &node.children);
+    let mut lines = content.lines();
+    let mut result = match lines.next() {
+        Some(first) if !first.is_empty() => format!(": {first}"),
+        _ => ":".to_string(),
+    };
+    for line in lines {
+        result.push('\n');
+        if !line.is_empty() {
+            result.push_str("    ");
+            result.push_str(line);
+        }
+    }
+    result
+}
+
 fn handle_code(state: &mut State, node: &mdast::Code) -> String {
-    let has_info = node.lang.is_some() || node.meta.is_some();
+    // Meta is only written after a language: the first word of the info
+    // string is always read as the language.
+    let node_meta = node.lang.as_ref().and(node.meta.as_ref());
+    let has_info = node.lang.is_some();
 
     // When fences are disabled and there's no info string, emit 4-space indented code
     // if the value is suitable. Port of mdast-util-to-markdown code.js +
@@ -349,11 +554,12 @@ fn handle_code(state: &mut State, node: &mdast::Code) -> String {
 
     let mut fence_char = state.options.fence;
 
-    // If the lang contains a backtick and we're using backtick fences, switch to tilde.
-    // A backtick in the info string would prematurely close the fence.
-    // Port of mdast-util-to-markdown code.js.
+    // If the lang or meta contains a backtick and we're using backtick fences,
+    // switch to tilde. A backtick in the info string would prematurely close
+    // the fence. Port of mdast-util-to-markdown code.js.
     let lang = node.lang.as_deref().unwrap_or("");
-    if lang.contains('`') && fence_char == '`' {
+    let info_has_backtick = lang.contains('`') || node_meta.is_some_and(|m| m.contains('`'));
+    if info_has_backtick && fence_char == '`' {
         fence_char = '~';
     }
 
@@ -378,16 +584,14 @@ fn handle_code(state: &mut State, node: &mdast::Code) -> String {
     // space in the info string separates lang from meta. Without meta, a space
     // in the lang is harmless (the entire info string IS the lang).
     // Port of mdast-util-to-markdown code.js.
-    let info = if node.meta.is_some() {
+    let info = if node_meta.is_some() {
         lang.replace(' ', "&#x20;")
     } else {
         lang.to_string()
     };
 
     // Replace newlines in meta with spaces — newlines would break the fence line.
-    let meta = node
-        .meta
-        .as_ref()
+    let meta = node_meta
         .map(|m| format!(" {}", m.replace('\n', " ")))
         .unwrap_or_default();
 
@@ -418,7 +622,7 @@ fn handle_definition(node: &mdast::Definition) -> String {
 // Phrasing (inline) handlers
 // ---------------------------------------------------------------------------
 
-fn handle_text(state: &mut State, node: &mdast::Text) -> String {
+pub(crate) fn handle_text(state: &mut State, node: &mdast::Text) -> String {
     // Escape Markdown syntax characters in phrasing content.
     // Port of mdast-util-to-markdown's `safe()` function.
     // When inside link text (`[…]`), also escape `]` to prevent premature
@@ -429,6 +633,8 @@ fn handle_text(state: &mut State, node: &mdast::Text) -> String {
     } else {
         super::escape::escape_phrasing(&node.value)
     };
+    // Escape the delimiters of enabled inline extensions (`^`, `==`, …).
+    let escaped = super::escape::escape_extensions(escaped, state.options);
     // Escape `|` inside table cells to prevent breaking table structure.
     // Port of mdast-util-to-markdown unsafe: {character: '|', inConstruct: 'tableCellContent'}
     let escaped = if state.in_table_cell {
@@ -436,6 +642,11 @@ fn handle_text(state: &mut State, node: &mdast::Text) -> String {
     } else {
         escaped
     };
+    let escaped = if state.escape_spaces {
+        Cow::Owned(escaped.replace(' ', "\\ "))
+    } else {
+        escaped
+    };
     // Apply at-break escaping if this text is at the start of a block.
     if state.at_break {
         state.at_break = false;
@@ -621,6 +832,76 @@ fn handle_delete(state: &mut State, node: &mdast::Delete) -> String {
     format!("~~{}~~", content)
 }
 
+pub(crate) fn handle_inline_math(state: &State, node: &mdast::InlineMath) -> String {
+    // An unescaped `$` would close the math early.
+    let value = super::escape::escape_unescaped(&node.value.replace('\n', " "), '$');
+    if state.in_table_cell {
+        format!("${}$", value.replace('|', "\\|"))
+    } else {
+        format!("${value}$")
+    }
+}
+
+/// Highlight, superscript, subscript or insert as `marker`-delimited
+/// extension syntax, a raw HTML `tag`, or just the content.
+fn handle_extension(
+    state: &mut State,
+    syntax: InlineSyntax,
+    marker: &str,
+    tag: &str,
+    children: &[Node],
+) -> String {
+    match syntax {
+        InlineSyntax::Fallback => super::phrasing::container_phrasing(state, children),
+        InlineSyntax::Html => {
+            let content = super::phrasing::container_phrasing(state, children);
+            format!("<{tag}>{content}</{tag}>")
+        }
+        InlineSyntax::Extension => {
+            // Delimiters must hug the content; edge whitespace goes outside.
+            let (lead, children, trail) = hoist_edge_whitespace(children);
+            // `^sup^` and `~sub~` cannot contain unescaped spaces.
+            let escape_spaces = state.escape_spaces;
+            state.escape_spaces = escape_spaces || marker.len() == 1;
+            let core = super::phrasing::container_phrasing(state, &children);
+            state.escape_spaces = escape_spaces;
+            if core.is_empty() {
+                return format!("{lead}{trail}");
+            }
+            format!("{lead}{marker}{core}{marker}{trail}")
+        }
+    }
+}
+
+/// Split leading whitespace off the first text child and trailing
+/// whitespace off the last.
+fn hoist_edge_whitespace(children: &[Node]) -> (String, Vec<Node>, String) {
+    let mut children = children.to_vec();
+    let mut lead = String::new();
+    let mut trail = String::new();
+    if let Some(Node::Text(text)) = children.first_mut() {
+        let start = text.value.len() - text.value.trim_start().len();
+        lead = text.value.drain(..start).collect();
+    }
+    if let Some(Node::Text(text)) = children.last_mut() {
+        let end = text.value.trim_end().len();
+        trail = text.value.split_off(end);
+    }
+    (lead, children, trail)
+}
+
+/// Whether phrasing is only a footnote reference (and whitespace).
+fn is_footnote_mark(children: &[Node]) -> bool {
+    let mut content = children.iter().filter(|child| match child {
+        Node::Text(text) => !text.value.trim().is_empty(),
+        _ => true,
+    });
+    matches!(
+        (content.next(), content.next()),
+        (Some(Node::FootnoteReference(_)), None)
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Table
 // ---------------------------------------------------------------------------
@@ -639,12 +920,21 @@ fn handle_table(state: &mut State, node: &mdast::Table) -> String {
                 .children
                 .iter()
                 .map(|cell| {
-                    if let Node::TableCell(tc) = cell {
+                    if let Node::TableCell(_) = cell {
                         state.in_table_cell = true;
-                        let content = super::phrasing::container_phrasing(state, &tc.children);
+                        let content = handle(state, cell);
                         state.in_table_cell = false;
-                        // Hard breaks (\<LF>) → space; bare newlines → &#xA; escape.
-                        content.trim().replace("\\\n", " ").replace('\n', "&#xA;")
+                        // Hard breaks (\<LF>) → space, or `<br>` to keep line
+                        // structure; bare newlines → &#xA; escape.
+                        let hard_break = if state.options.table_cell_breaks {
+                            "<br>"
+                        } else {
+                            " "
+                        };
+                        content
+                            .trim()
+                            .replace("\\\n", hard_break)
+                            .replace('\n', "&#xA;")
                     } else {
                         String::new()
                     }
@@ -658,6 +948,13 @@ fn handle_table(state: &mut State, node: &mdast::Table) -> String {
         return String::new();
     }
 
+    match state.options.table_style {
+        TableStyle::Pipe => {}
+        TableStyle::Compact => return compact_table(&rows, &node.align),
+        TableStyle::KeyValue => return key_value_table(&rows, state.options.bullet),
+        TableStyle::Grid => return grid_table(&rows, &node.align),
+    }
+
     // Determine column count and widths.
     let col_count = rows.iter().map(|r| r.len()).max().unwrap_or(0);
     let mut col_widths = vec![1usize; col_count]; // minimum 1
@@ -714,8 +1011,17 @@ fn format_row(
 }
 
 fn pad_cell(content: &str, width: usize, align: Option<crate::mdast::AlignKind>) -> String {
+    pad_to(content, content.chars().count(), width, align)
+}
+
+/// Pad `content`, which occupies `len` columns, to `width` columns.
+fn pad_to(
+    content: &str,
+    len: usize,
+    width: usize,
+    align: Option<crate::mdast::AlignKind>,
+) -> String {
     use crate::mdast::AlignKind;
-    let len = content.chars().count();
     let padding = width.saturating_sub(len);
     match align {
         Some(AlignKind::Right) => {
@@ -758,6 +1064,112 @@ fn format_separator(width: usize, align: Option<crate::mdast::AlignKind>) -> Str
     }
 }
 
+/// `|a|b|` rows with a minimal delimiter row and no padding.
+fn compact_table(rows: &[Vec<String>], aligns: &[Option<crate::mdast::AlignKind>]) -> String {
+    use crate::mdast::AlignKind;
+    let col_count = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let line = |cells: Vec<&str>| format!("|{}|", cells.join("|"));
+    let row = |row: &Vec<String>| {
+        line(
+            (0..col_count)
+                .map(|i| row.get(i).map_or("", String::as_str))
+                .collect(),
+        )
+    };
+    let separator = (0..col_count)
+        .map(|i| match aligns.get(i).copied().flatten() {
+            Some(AlignKind::Left) => ":-",
+            Some(AlignKind::Right) => "-:",
+            Some(AlignKind::Center) => ":-:",
+            None => "-",
+        })
+        .collect();
+
+    let mut lines = vec![row(&rows[0]), line(separator)];
+    lines.extend(rows.iter().skip(1).map(row));
+    lines.join("\n")
+}
+
+/// Each body row as a list of `header: value` items. Rows alternate
+/// bullets, as adjacent lists do, so parsers keep them apart. Empty values
+/// are left out; headerless columns are named `Column N`. A table without
+/// body values lists its header names.
+fn key_value_table(rows: &[Vec<String>], bullet: char) -> String {
+    let header = &rows[0];
+    let name = |i: usize| match header.get(i).map(|h| h.trim()) {
+        Some(h) if !h.is_empty() => h.to_string(),
+        _ => format!("Column {}", i + 1),
+    };
+    let other = if bullet == '*' { '-' } else { '*' };
+    let mut blocks: Vec<Vec<String>> = rows
+        .iter()
+        .skip(1)
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(i, value)| format!("{}: {value}", name(i)))
+                .collect::<Vec<_>>()
+        })
+        .filter(|items| !items.is_empty())
+        .collect();
+    if blocks.is_empty() {
+        blocks.push(header.iter().filter(|h| !h.is_empty()).cloned().collect());
+    }
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(i, items)| {
+            let bullet = if i % 2 == 0 { bullet } else { other };
+            items
+                .iter()
+                .map(|item| format!("{bullet} {item}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Fixed-width grid (Pandoc grid table syntax), measured in terminal
+/// columns so CJK and emoji line up.
+fn grid_table(rows: &[Vec<String>], aligns: &[Option<crate::mdast::AlignKind>]) -> String {
+    let col_count = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let cell = |row: &Vec<String>, i: usize| row.get(i).map_or(String::new(), Clone::clone);
+    let mut widths = vec![1usize; col_count];
+    for row in rows {
+        for (i, width) in widths.iter_mut().enumerate() {
+            *width = (*width).max(super::width::str_width(&cell(row, i)));
+        }
+    }
+    let border = |fill: &str| {
+        let parts: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+        format!("+{}+", parts.join("+"))
+    };
+    let line = |row: &Vec<String>| {
+        let parts: Vec<String> = (0..col_count)
+            .map(|i| {
+                let content = cell(row, i);
+                let len = super::width::str_width(&content);
+                let align = aligns.get(i).copied().flatten();
+                format!(" {} ", pad_to(&content, len, widths[i], align))
+            })
+            .collect();
+        format!("|{}|", parts.join("|"))
+    };
+
+    if rows.len() == 1 {
+        return [border("-"), line(&rows[0]), border("-")].join("\n");
+    }
+    let mut lines = vec![border("-"), line(&rows[0]), border("=")];
+    for row in rows.iter().skip(1) {
+        lines.push(line(row));
+        lines.push(border("-"));
+    }
+    lines.join("\n")
+}
+
 // ---------------------------------------------------------------------------
 // Footnotes
 // ---------------------------------------------------------------------------
@@ -799,6 +1211,16 @@ fn handle_yaml(node: &mdast::Yaml) -> String {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Format an attribute block: `{#id .class1 .class2}`.
+fn format_attributes(attributes: &mdast::Attributes) -> String {
+    let mut parts = Vec::new();
+    if let Some(id) = &attributes.id {
+        parts.push(format!("#{id}"));
+    }
+    parts.extend(attributes.classes.iter().map(|class| format!(".{class}")));
+    format!("{{{}}}", parts.join(" "))
+}
+
 /// Check whether a code value can be formatted as a 4-space indented block.
 /// Port of mdast-util-to-markdown format-code-as-indented.js.
 /// Returns false for empty values, all-whitespace values, or values that
@@ -4,12 +4,15 @@
 // Walks an MDAST tree and emits a Markdown string. All formatting choices
 // (heading style, list markers, emphasis characters, etc.) live here.
 
+pub(crate) mod custom;
 pub(crate) mod escape;
 pub(crate) mod flow;
 pub(crate) mod handlers;
 pub(crate) mod phrasing;
+pub(crate) mod width;
 
 use crate::mdast::Node;
+use custom::NodeHandlers;
 
 /// Heading style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -33,6 +36,54 @@ pub enum ListItemIndent {
     Mixed,
 }
 
+/// How tables are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStyle {
+    /// GFM pipe table with padded, aligned columns (default).
+    #[default]
+    Pipe,
+    /// GFM pipe table without padding (`|a|b|`), to save space.
+    Compact,
+    /// Each row as a list of `header: value` items, alternating bullets
+    /// between rows.
+    KeyValue,
+    /// Fixed-width ASCII grid (Pandoc grid table syntax), measuring wide
+    /// CJK and emoji characters as two columns.
+    Grid,
+}
+
+/// How an extended inline construct (highlight, superscript, subscript,
+/// insert) is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InlineSyntax {
+    /// Plain CommonMark: highlight as emphasis, the others as their plain
+    /// content (default). The HTML converter keeps its usual mapping.
+    #[default]
+    Fallback,
+    /// Extension syntax: `==mark==`, `^sup^`, `~sub~`, `++ins++`.
+    Extension,
+    /// Raw HTML: `<mark>`, `<sup>`, `<sub>`, `<ins>`.
+    Html,
+}
+
+/// How admonitions (notes, tips, warnings) are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdmonitionStyle {
+    /// Not recognized: the HTML converter keeps them as plain content
+    /// (default). Admonition nodes are written as blockquotes.
+    #[default]
+    Off,
+    /// GitHub alerts: `> [!WARNING]`. GitHub knows `note`, `tip`,
+    /// `important`, `warning` and `caution`; other kinds are written as
+    /// notes.
+    Alert,
+    /// Container directives (remark-directive, Docusaurus):
+    /// `:::warning[Title]` … `:::`.
+    Directive,
+    /// Obsidian callouts: `> [!warning] Title`.
+    Callout,
+}
+
 /// Serializer configuration.
 #[derive(Debug, Clone)]
 pub struct StringifyOptions {
@@ -51,6 +102,30 @@ pub struct StringifyOptions {
     pub quote: char,
     pub fences: bool,
     pub resource_link: bool,
+    /// How tables are written: padded pipes, compact pipes, key-value
+    /// lists, or a grid.
+    pub table_style: TableStyle,
+    /// Keep line structure inside table cells: hard breaks and paragraph
+    /// boundaries become `<br>`, and list items become `<br>`-separated
+    /// `• item` (or `1. item`) lines. Otherwise block content in cells is
+    /// run together.
+    pub table_cell_breaks: bool,
+    /// How `Highlight` nodes are written: `==mark==`, `<mark>`, or emphasis.
+    pub highlight: InlineSyntax,
+    /// How `Superscript` nodes are written: `^sup^`, `<sup>`, or plain text.
+    pub superscript: InlineSyntax,
+    /// How `Subscript` nodes are written: `~sub~`, `<sub>`, or plain text.
+    pub subscript: InlineSyntax,
+    /// How `Insert` nodes are written: `++ins++`, `<ins>`, or plain text.
+    pub insert: InlineSyntax,
+    /// Math is written as `$…$` and `$$` blocks; `$` in text is escaped.
+    pub math: bool,
+    /// `<dl>` is written as a definition list (`Term` / `: definition`)
+    /// rather than a bulleted list.
+    pub definition_lists: bool,
+    /// How `Admonition` nodes are written: GitHub alerts, `:::` directives,
+    /// or Obsidian callouts.
+    pub admonition_style: AdmonitionStyle,
 }
 
 impl Default for StringifyOptions {
@@ -71,6 +146,15 @@ impl Default for StringifyOptions {
             quote: '"',
             fences: true,
             resource_link: false,
+            table_style: TableStyle::Pipe,
+            table_cell_breaks: false,
+            highlight: InlineSyntax::Fallback,
+            superscript: InlineSyntax::Fallback,
+            subscript: InlineSyntax::Fallback,
+            insert: InlineSyntax::Fallback,
+            math: false,
+            definition_lists: false,
+            admonition_style: AdmonitionStyle::Off,
         }
     }
 }
@@ -78,6 +162,8 @@ impl Default for StringifyOptions {
 /// Serializer state threaded through all handlers.
 pub(crate) struct State<'a> {
     pub options: &'a StringifyOptions,
+    /// Custom node handlers, consulted before the built-in dispatch.
+    pub handlers: &'a NodeHandlers,
     /// Current list bullet (may switch to avoid conflicts).
     pub bullet_current: Option<char>,
     /// Previous list's bullet (for alternation).
@@ -96,25 +182,42 @@ pub(crate) struct State<'a> {
     /// Whether we are currently inside a table cell.
     /// When true, `|` must be escaped to avoid breaking table structure.
     pub in_table_cell: bool,
+    /// Whether spaces in text are escaped (`\ `), inside `^sup^` and
+    /// `~sub~`, which cannot contain unescaped spaces.
+    pub escape_spaces: bool,
+    /// Whether we are inside a GitHub alert, which cannot nest: inner
+    /// admonitions are written as ordinary blockquotes.
+    pub in_alert: bool,
+    /// Whether the list whose items are being written is spread, which
+    /// makes every item spread.
+    pub in_spread_list: bool,
 }
 
 impl<'a> State<'a> {
-    pub fn new(options: &'a StringifyOptions) -> Self {
+    pub fn new(options: &'a StringifyOptions, handlers: &'a NodeHandlers) -> Self {
         Self {
             options,
+            handlers,
             bullet_current: None,
             bullet_last_used: None,
             ordered_bullet_last_used: None,
             at_break: false,
             in_link_text: false,
             in_table_cell: false,
+            escape_spaces: false,
+            in_alert: false,
+            in_spread_list: false,
         }
     }
 }
 
 /// Serialize an MDAST tree to a Markdown string.
-pub(crate) fn stringify(node: &Node, options: &StringifyOptions) -> String {
-    let mut state = State::new(options);
+pub(crate) fn stringify(
+    node: &Node,
+    options: &StringifyOptions,
+    handlers: &NodeHandlers,
+) -> String {
+    let mut state = State::new(options, handlers);
     let mut output = handlers::handle(&mut state, node);
 
     // Ensure trailing newline (only if non-empty).
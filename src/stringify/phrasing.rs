@@ -12,7 +12,17 @@ pub(crate) fn container_phrasing(state: &mut State, children: &[Node]) -> String
     let mut parts: Vec<String> = Vec::with_capacity(children.len());
 
     for child in children {
-        parts.push(super::handlers::handle(state, child));
+        // Display math cannot sit in a line of text; it is written inline.
+        let part = match child {
+            Node::Math(math) => super::handlers::handle_inline_math(
+                state,
+                &crate::mdast::InlineMath {
+                    value: math.value.clone(),
+                },
+            ),
+            _ => super::handlers::handle(state, child),
+        };
+        parts.push(part);
     }
 
     // Trim whitespace adjacent to hard breaks ("\\\n"):
@@ -0,0 +1,124 @@
+// Display width of text in a monospace terminal.
+//
+// A small subset of Unicode East Asian Width and emoji rules, enough to line
+// up grid tables: wide (CJK, Hangul, fullwidth forms, emoji) characters take
+// two columns, combining marks and joiners none.
+
+/// Terminal columns taken by `text`.
+pub(crate) fn str_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        width += match c {
+            // ZWJ sequences (👩‍💻) render as one glyph.
+            _ if previous == Some('\u{200D}') => 0,
+            // VS16 turns a text-style symbol (❤) into a wide emoji.
+            '\u{FE0F}' => usize::from(previous.is_some_and(|p| char_width(p) == 1)),
+            // The second regional indicator of a flag.
+            _ if is_regional_indicator(c) && previous.is_some_and(is_regional_indicator) => {
+                previous = None;
+                continue;
+            }
+            _ => char_width(c),
+        };
+        previous = Some(c);
+    }
+    width
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn char_width(c: char) -> usize {
+    let cp = c as u32;
+    if c.is_control() {
+        return 0;
+    }
+    match cp {
+        // Combining marks, zero-width spaces/joiners, variation selectors.
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0E31
+        | 0x0E34..=0x0E3A
+        | 0x0E47..=0x0E4E
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0xE0100..=0xE01EF => 0,
+        // East Asian Wide and Fullwidth.
+        0x1100..=0x115F
+        | 0x231A..=0x231B
+        | 0x23E9..=0x23EC
+        | 0x25FD..=0x25FE
+        | 0x2614..=0x2615
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26CE
+        | 0x26D4
+        | 0x26EA
+        | 0x26F2..=0x26F5
+        | 0x26FA
+        | 0x26FD
+        | 0x2705
+        | 0x270A..=0x270B
+        | 0x2728
+        | 0x274C
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27B0
+        | 0x27BF
+        | 0x2B1B..=0x2B1C
+        | 0x2B50
+        | 0x2B55
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xA960..=0xA97F
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE10..=0xFE19
+        | 0xFE30..=0xFE6F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F004
+        | 0x1F0CF
+        | 0x1F18E
+        | 0x1F191..=0x1F19A
+        | 0x1F1E6..=0x1F1FF
+        | 0x1F200..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F7E0..=0x1F7EB
+        | 0x1F90C..=0x1F9FF
+        | 0x1FA70..=0x1FAFF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(str_width("abc"), 3);
+        assert_eq!(str_width("日本語"), 6);
+        assert_eq!(str_width("한국"), 4);
+        assert_eq!(str_width("ｆｕｌｌ"), 8);
+        assert_eq!(str_width("e\u{301}"), 1);
+        assert_eq!(str_width("🎉"), 2);
+        assert_eq!(str_width("❤\u{FE0F}"), 2);
+        assert_eq!(str_width("👩\u{200D}💻"), 2);
+        assert_eq!(str_width("🇯🇵"), 2);
+    }
+}
@@ -1,6 +1,14 @@
 // End-to-end API tests for html2markdown.
 
-use html2markdown::{convert, convert_with, HeadingStyle, Options};
+use html2markdown::mdast::{self, AlignKind, Node};
+use html2markdown::{
+    convert, convert_with, extract_tables, html_to_document, html_to_mdast, table_losses,
+    AdmonitionStyle, Converter, DefinitionPlacement, Element, HeadingStyle, InlineSyntax,
+    KeepAttributes, LinkStyle, Options, Selector, Serializer, StringifyContext, StringifyOptions,
+    TableCaption, TableData, TableLoss, TableStrategy, TableStyle, TransformContext,
+};
+use pretty_assertions::assert_eq;
+use serde_json::json;
 
 #[test]
 fn test_empty_input() {
@@ -65,3 +73,1989 @@ fn roundtrip_raw_html_invalid() {
         }
     }
 }
+
+// ---------------------------------------------------------------------------
+// Custom element handlers
+// ---------------------------------------------------------------------------
+
+fn text(value: &str) -> Node {
+    Node::Text(mdast::Text {
+        value: value.to_string(),
+    })
+}
+
+#[test]
+fn test_custom_handlers_tag_handler_overrides_builtin() {
+    let converter = Converter::new(Options::new()).with_handler(
+        "aside",
+        |cx: &mut TransformContext, el: &Element| {
+            let children = cx.all(el);
+            vec![Node::Blockquote(mdast::Blockquote {
+                children: cx.wrap(children),
+            })]
+        },
+    );
+    let md = converter.convert("<p>Intro</p><aside>Careful <em>now</em></aside>");
+    assert_eq!(md, "Intro\n\n> Careful *now*\n");
+}
+
+#[test]
+fn test_custom_handlers_custom_element_handler_reads_attributes() {
+    let converter = Converter::new(Options::new()).with_handler(
+        "my-widget",
+        |_: &mut TransformContext, el: &Element| {
+            let name = el.attr("data-name").unwrap_or_default();
+            vec![Node::Paragraph(mdast::Paragraph {
+                children: vec![text(&format!("[widget: {name}]"))],
+            })]
+        },
+    );
+    let md = converter.convert(r#"<my-widget data-name="clock">ignored</my-widget>"#);
+    assert_eq!(md, "\\[widget: clock]\n");
+}
+
+#[test]
+fn test_custom_handlers_predicate_handler_matches_class() {
+    let converter = Converter::new(Options::new()).with_handler_when(
+        |el: &Element| el.tag() == "div" && el.has_class("callout"),
+        |cx: &mut TransformContext, el: &Element| {
+            let mut children = vec![Node::Strong(mdast::Strong {
+                children: vec![text("Note:")],
+            })];
+            children.push(text(" "));
+            children.extend(cx.all(el));
+            vec![Node::Paragraph(mdast::Paragraph { children })]
+        },
+    );
+    let md = converter.convert(r#"<div class="box callout">Read this</div><div>Plain</div>"#);
+    assert_eq!(md, "**Note:** Read this\n\nPlain\n");
+}
+
+#[test]
+fn test_custom_handlers_handler_can_fall_back_to_builtin() {
+    let converter = Converter::new(Options::new()).with_handler(
+        "a",
+        |cx: &mut TransformContext, el: &Element| {
+            if el.attr("rel").as_deref() == Some("nofollow") {
+                return cx.all(el);
+            }
+            cx.default(el)
+        },
+    );
+    let md =
+        converter.convert(r#"<p><a href="/x" rel="nofollow">ad</a> and <a href="/y">real</a></p>"#);
+    assert_eq!(md, "ad and [real](/y)\n");
+}
+
+#[test]
+fn test_custom_handlers_later_registration_wins() {
+    let converter = Converter::new(Options::new())
+        .with_handler("hr", |_: &mut TransformContext, _: &Element| {
+            vec![text("first")]
+        })
+        .with_handler("HR", |_: &mut TransformContext, _: &Element| {
+            vec![text("second")]
+        });
+    assert_eq!(converter.convert("<hr>"), "second\n");
+}
+
+#[test]
+fn test_custom_handlers_handlers_apply_to_descendants_of_handled_elements() {
+    let converter = Converter::new(Options::new())
+        .with_handler("section", |cx: &mut TransformContext, el: &Element| {
+            let children = cx.all(el);
+            vec![Node::Blockquote(mdast::Blockquote {
+                children: cx.wrap(children),
+            })]
+        })
+        .with_handler("b", |cx: &mut TransformContext, el: &Element| {
+            vec![Node::Emphasis(mdast::Emphasis {
+                children: cx.all(el),
+            })]
+        });
+    let md = converter.convert("<section><p>a <b>b</b></p></section>");
+    assert_eq!(md, "> a *b*\n");
+}
+
+#[test]
+fn test_custom_handlers_converter_without_handlers_matches_convert_with() {
+    let html = "<h1>T</h1><ul><li>a</li><li>b</li></ul>";
+    let options = Options::new().with_bullet('-');
+    let converter = Converter::new(options.clone());
+    assert_eq!(
+        converter.convert(html),
+        html2markdown::convert_with(html, &options)
+    );
+    assert_eq!(
+        converter.html_to_mdast(html),
+        html2markdown::html_to_mdast(html, &options)
+    );
+}
+
+#[test]
+fn test_custom_handlers_element_children_skip_text() {
+    let converter = Converter::new(Options::new()).with_handler(
+        "ul",
+        |_: &mut TransformContext, el: &Element| {
+            let count = el.children().len();
+            vec![text(&format!("{count} items"))]
+        },
+    );
+    assert_eq!(
+        converter.convert("<ul>\n<li>a</li>\n<li>b</li>\n</ul>"),
+        "2 items\n"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Custom node handlers (serializer)
+// ---------------------------------------------------------------------------
+
+fn figure(cx: &mut StringifyContext, node: &Node) -> String {
+    match node {
+        Node::Image(image) => {
+            let alt = cx.escape(&image.alt);
+            format!("{{{{< figure src=\"{}\" alt=\"{}\" >}}}}", image.url, alt)
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_custom_node_handlers_image_written_as_shortcode() {
+    let tree = html_to_mdast(
+        r#"<p>See <img src="a.png" alt="a*b"> here</p>"#,
+        &Options::new(),
+    );
+    let serializer = Serializer::new(StringifyOptions::default()).with_handler("image", figure);
+    assert_eq!(
+        serializer.stringify(&tree),
+        "See {{< figure src=\"a.png\" alt=\"a\\*b\" >}} here\n"
+    );
+}
+
+#[test]
+fn test_custom_node_handlers_link_rewritten_through_default() {
+    let converter = Converter::new(Options::new()).with_node_handler(
+        "link",
+        |cx: &mut StringifyContext, node: &Node| match node {
+            Node::Link(link) => {
+                let mut link = link.clone();
+                if let Some(rest) = link.url.strip_prefix("https://old.example/") {
+                    link.url = format!("https://new.example/{rest}");
+                }
+                cx.default(&Node::Link(link))
+            }
+            _ => unreachable!(),
+        },
+    );
+    let md = converter.convert(r#"<p><a href="https://old.example/docs">Docs</a></p>"#);
+    assert_eq!(md, "[Docs](https://new.example/docs)\n");
+}
+
+#[test]
+fn test_custom_node_handlers_predicate_handler_uses_containers() {
+    let serializer = Serializer::new(StringifyOptions::default()).with_handler_when(
+        |node: &Node| matches!(node, Node::Heading(h) if h.depth == 1),
+        |cx: &mut StringifyContext, node: &Node| match node {
+            Node::Heading(h) => {
+                let title = cx.container_phrasing(&h.children);
+                format!("title: {title}")
+            }
+            _ => unreachable!(),
+        },
+    );
+    let tree = html_to_mdast("<h1>A <em>b</em></h1><h2>C</h2>", &Options::new());
+    assert_eq!(serializer.stringify(&tree), "title: A *b*\n\n## C\n");
+}
+
+#[test]
+fn test_custom_node_handlers_escape_respects_table_cells() {
+    let serializer = Serializer::new(StringifyOptions::default()).with_handler(
+        "inlineCode",
+        |cx: &mut StringifyContext, node: &Node| match node {
+            Node::InlineCode(code) => cx.escape(&code.value),
+            _ => unreachable!(),
+        },
+    );
+    let tree = html_to_mdast(
+        "<table><tr><th>x</th></tr><tr><td><code>a|b</code></td></tr></table>",
+        &Options::new(),
+    );
+    let md = serializer.stringify(&tree);
+    assert!(md.contains("| a\\|b |"), "pipe should be escaped: {md:?}");
+}
+
+#[test]
+fn test_custom_node_handlers_list_items_and_table_cells() {
+    let serializer = Serializer::new(StringifyOptions::default())
+        .with_handler("listItem", |cx: &mut StringifyContext, node: &Node| {
+            format!("({})", cx.default(node))
+        })
+        .with_handler("tableCell", |cx: &mut StringifyContext, node: &Node| {
+            cx.default(node).to_uppercase()
+        });
+    let tree = html_to_mdast(
+        "<ul><li>a</li><li><p>b</p><p>c</p></li></ul>\
+         <table><tr><th>x</th></tr><tr><td>y</td></tr></table>",
+        &Options::new(),
+    );
+    assert_eq!(
+        serializer.stringify(&tree),
+        "* (a)\n\n* (b\n\n  c)\n\n| X |\n| - |\n| Y |\n"
+    );
+}
+
+#[test]
+fn test_custom_node_handlers_serializer_without_handlers_matches_mdast_to_string() {
+    let tree = html_to_mdast(
+        "<ol><li>a</li></ol><blockquote>q</blockquote>",
+        &Options::new(),
+    );
+    let options = StringifyOptions::default();
+    assert_eq!(
+        Serializer::new(options.clone()).stringify(&tree),
+        html2markdown::mdast_to_string(&tree, &options)
+    );
+}
+
+const SELECTORS_PAGE: &str = r#"
+    <nav><a href="/">Home</a></nav>
+    <article>
+      <h1>Title</h1>
+      <div class="post-body">
+        <p>First <a href="a.html">link</a>.</p>
+        <div class="share-buttons"><a href="https://share.test">Share</a></div>
+        <p>Second.</p>
+      </div>
+    </article>
+    <div class="post-body"><p>Outside article.</p></div>"#;
+
+#[test]
+fn test_selectors_root_selector_picks_subtree() {
+    let options = Options::new().with_root_selector("article .post-body");
+    assert_eq!(
+        convert_with(SELECTORS_PAGE, &options),
+        "First [link](a.html).\n\n[Share](https://share.test)\n\nSecond.\n"
+    );
+}
+
+#[test]
+fn test_selectors_root_selector_converts_every_match_in_order() {
+    let options = Options::new().with_root_selector(".post-body");
+    let md = convert_with(SELECTORS_PAGE, &options);
+    assert!(md.starts_with("First"), "{md:?}");
+    assert!(md.ends_with("Second.\n\nOutside article.\n"), "{md:?}");
+}
+
+#[test]
+fn test_selectors_root_selector_without_match_is_empty() {
+    let options = Options::new().with_root_selector("main");
+    assert_eq!(convert_with(SELECTORS_PAGE, &options), "");
+}
+
+#[test]
+fn test_selectors_exclude_selectors_skip_subtrees() {
+    let options = Options::new()
+        .with_exclude_selector(".share-buttons")
+        .with_exclude_selector("nav");
+    let md = convert_with(SELECTORS_PAGE, &options);
+    assert!(!md.contains("Share"), "{md:?}");
+    assert!(!md.contains("Home"), "{md:?}");
+    assert!(md.contains("# Title"), "{md:?}");
+}
+
+#[test]
+fn test_selectors_root_and_exclude_combine() {
+    let options = Options::new()
+        .with_root_selector("article .post-body")
+        .with_exclude_selector(".share-buttons");
+    assert_eq!(
+        convert_with(SELECTORS_PAGE, &options),
+        "First [link](a.html).\n\nSecond.\n"
+    );
+}
+
+#[test]
+fn test_selectors_root_selector_still_honors_base() {
+    let html = r#"<head><base href="https://example.com/blog/"></head>
+        <body><main><a href="post.html">Post</a></main></body>"#;
+    let options = Options::new().with_root_selector("main");
+    assert_eq!(
+        convert_with(html, &options),
+        "[Post](https://example.com/blog/post.html)\n"
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid selector")]
+fn test_selectors_invalid_selector_panics_in_builder() {
+    let _ = Options::new().with_exclude_selector("p::before");
+}
+
+#[test]
+fn test_selectors_element_matches_selector_in_handlers() {
+    let ad = Selector::parse("aside.ad, aside[data-sponsored]").unwrap();
+    let converter = Converter::new(Options::new()).with_handler(
+        "aside",
+        move |cx: &mut html2markdown::TransformContext, el: &Element| {
+            if el.matches(&ad) {
+                Vec::new()
+            } else {
+                cx.default(el)
+            }
+        },
+    );
+    assert_eq!(
+        converter.convert("<aside class=ad>Buy</aside><aside>Note</aside>"),
+        "Note\n"
+    );
+}
+
+const ARTICLE: &str = r#"<html><body>
+    <header class="site-header"><a href="/">Home</a> <a href="/about">About</a></header>
+    <nav><ul><li><a href="/a">Archive</a></li><li><a href="/b">Tags</a></li></ul></nav>
+    <div id="main">
+      <h1>Understanding ownership</h1>
+      <div class="post-content">
+        <p>Ownership is a set of rules that govern how a program manages memory, and it is the most unique feature of the language.</p>
+        <p>Some languages have garbage collection, while others require the programmer to explicitly allocate and free memory.</p>
+        <p>Rust uses a third approach, where memory is managed through a system of ownership with rules the compiler checks.</p>
+      </div>
+      <div class="share-widget"><a href="https://share.test/x">Share on X</a>, <a href="https://share.test/y">Share on Y</a></div>
+    </div>
+    <aside class="sidebar"><h2>Related posts</h2><p>Lifetimes, borrowing and other topics you might like, all in one place.</p></aside>
+    <footer><p>Copyright 2024, Example Corp. All rights reserved, forever and ever.</p></footer>
+    </body></html>"#;
+
+#[test]
+fn test_main_content_extracts_article_body() {
+    let md = convert_with(ARTICLE, &Options::new().with_main_content(true));
+    assert!(md.contains("# Understanding ownership"), "{md}");
+    assert!(md.contains("Ownership is a set of rules"), "{md}");
+    assert!(md.contains("system of ownership"), "{md}");
+    for boilerplate in ["Home", "Archive", "Share on", "Related posts", "Copyright"] {
+        assert!(!md.contains(boilerplate), "{boilerplate} leaked: {md}");
+    }
+}
+
+#[test]
+fn test_main_content_disabled_by_default() {
+    let md = convert_with(ARTICLE, &Options::new());
+    assert!(md.contains("Archive"), "{md}");
+    assert!(md.contains("Copyright"), "{md}");
+}
+
+#[test]
+fn test_main_content_falls_back_to_body_without_candidate() {
+    let html = "<html><head><title>T</title></head><body><h1>Hi</h1><p>Short.</p></body></html>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_main_content(true)),
+        "# Hi\n\nShort.\n"
+    );
+}
+
+#[test]
+fn test_main_content_root_selector_takes_precedence() {
+    let options = Options::new()
+        .with_main_content(true)
+        .with_root_selector("footer");
+    assert_eq!(
+        convert_with(ARTICLE, &options),
+        "Copyright 2024, Example Corp. All rights reserved, forever and ever.\n"
+    );
+}
+
+#[test]
+fn test_main_content_exclude_selectors_apply_inside_extracted_content() {
+    let options = Options::new()
+        .with_main_content(true)
+        .with_exclude_selector("h1");
+    let md = convert_with(ARTICLE, &options);
+    assert!(!md.contains("Understanding"), "{md}");
+    assert!(md.contains("Ownership is a set of rules"), "{md}");
+}
+
+const METADATA_PAGE: &str = r#"<!doctype html>
+    <html lang="en-GB">
+    <head>
+      <base href="https://example.com/blog/">
+      <title>  Ownership:
+         a primer </title>
+      <meta name="description" content="What ownership means.">
+      <meta name="author" content="Jane Doe">
+      <meta property="article:published_time" content="2024-03-01T09:30:00Z">
+      <meta property="og:title" content="OG title">
+      <meta property="og:image" content="img/cover.png">
+      <meta property="og:site_name" content="Example Blog">
+      <meta name="twitter:card" content="summary">
+      <meta name="keywords" content="rust, memory,  ownership">
+      <link rel="canonical" href="ownership.html">
+    </head>
+    <body><h1>Ownership</h1><p>Body.</p></body>
+    </html>"#;
+
+#[test]
+fn test_metadata_collects_metadata_next_to_tree() {
+    let doc = html_to_document(METADATA_PAGE, &Options::new());
+    let meta = &doc.metadata;
+    assert_eq!(meta.title.as_deref(), Some("Ownership: a primer"));
+    assert_eq!(meta.description.as_deref(), Some("What ownership means."));
+    assert_eq!(meta.author.as_deref(), Some("Jane Doe"));
+    assert_eq!(meta.published.as_deref(), Some("2024-03-01T09:30:00Z"));
+    assert_eq!(
+        meta.canonical.as_deref(),
+        Some("https://example.com/blog/ownership.html")
+    );
+    assert_eq!(meta.lang.as_deref(), Some("en-GB"));
+    assert_eq!(meta.site_name.as_deref(), Some("Example Blog"));
+    assert_eq!(
+        meta.image.as_deref(),
+        Some("https://example.com/blog/img/cover.png")
+    );
+    assert_eq!(meta.keywords, ["rust", "memory", "ownership"]);
+    assert_eq!(meta.open_graph["title"], "OG title");
+    assert_eq!(meta.twitter["card"], "summary");
+    // The tree itself is unchanged unless frontmatter is requested.
+    assert_eq!(
+        doc.tree,
+        html2markdown::html_to_mdast(METADATA_PAGE, &Options::new())
+    );
+}
+
+#[test]
+fn test_metadata_emits_frontmatter() {
+    let md = convert_with(METADATA_PAGE, &Options::new().with_frontmatter(true));
+    assert_eq!(
+        md,
+        "---\n\
+         title: \"Ownership: a primer\"\n\
+         description: What ownership means.\n\
+         author: Jane Doe\n\
+         date: 2024-03-01T09:30:00Z\n\
+         canonical: https://example.com/blog/ownership.html\n\
+         lang: en-GB\n\
+         site_name: Example Blog\n\
+         image: https://example.com/blog/img/cover.png\n\
+         keywords: [rust, memory, ownership]\n\
+         ---\n\n\
+         # Ownership\n\nBody.\n"
+    );
+}
+
+#[test]
+fn test_metadata_falls_back_to_open_graph() {
+    let html = r#"<head><meta property="og:title" content="From OG">
+        <meta name="twitter:description" content="From Twitter"></head><p>x</p>"#;
+    let meta = html_to_document(html, &Options::new()).metadata;
+    assert_eq!(meta.title.as_deref(), Some("From OG"));
+    assert_eq!(meta.description.as_deref(), Some("From Twitter"));
+}
+
+#[test]
+fn test_metadata_no_frontmatter_without_metadata() {
+    let md = convert_with("<p>Just text</p>", &Options::new().with_frontmatter(true));
+    assert_eq!(md, "Just text\n");
+}
+
+#[test]
+fn test_metadata_svg_title_is_not_document_title() {
+    let html = "<body><svg><title>Icon</title></svg><p>x</p></body>";
+    let meta = html_to_document(html, &Options::new()).metadata;
+    assert_eq!(meta.title, None);
+    assert!(meta.is_empty());
+}
+
+const STRUCTURED_DATA_PAGE: &str = r#"<html><head>
+    <base href="https://example.com/">
+    <title>Site | Fallback title</title>
+    <script type="application/ld+json">
+      {"@context": "https://schema.org", "@graph": [
+        {"@type": "BreadcrumbList", "itemListElement": [{"name": "Home"}, {"name": "Blog"}]},
+        {"@type": "Article", "headline": "Real headline",
+         "author": {"@type": "Person", "name": "Ann Lee"},
+         "datePublished": "2024-05-01"}
+      ]}
+    </script>
+    <script type="application/ld+json">{ broken</script>
+    </head><body>
+    <div itemscope itemtype="https://schema.org/Recipe">
+      <h1 itemprop="name">Pancakes</h1>
+      <img itemprop="image" src="p.jpg" alt="">
+      <p>By <span itemprop="author" itemscope itemtype="https://schema.org/Person"><span itemprop="name">Bo</span></span></p>
+      <time itemprop="cookTime" datetime="PT20M">20 minutes</time>
+      <meta itemprop="recipeYield" content="4">
+    </div>
+    </body></html>"#;
+
+#[test]
+fn test_structured_data_collects_json_ld_and_microdata() {
+    let data = html_to_document(STRUCTURED_DATA_PAGE, &Options::new()).structured_data;
+    assert_eq!(data.json_ld.len(), 1, "broken block is skipped");
+    assert_eq!(data.json_ld[0]["@graph"][1]["headline"], "Real headline");
+    assert_eq!(
+        data.microdata,
+        vec![json!({
+            "type": ["https://schema.org/Recipe"],
+            "properties": {
+                "name": ["Pancakes"],
+                "image": ["https://example.com/p.jpg"],
+                "author": [{
+                    "type": ["https://schema.org/Person"],
+                    "properties": {"name": ["Bo"]}
+                }],
+                "cookTime": ["PT20M"],
+                "recipeYield": ["4"]
+            }
+        })]
+    );
+}
+
+#[test]
+fn test_structured_data_find_prefers_json_ld() {
+    let data = html_to_document(STRUCTURED_DATA_PAGE, &Options::new()).structured_data;
+    assert_eq!(data.find("headline"), Some(json!("Real headline")));
+    assert_eq!(data.find("author.name"), Some(json!("Ann Lee")));
+    assert_eq!(data.find("cookTime"), Some(json!("PT20M")));
+    assert_eq!(
+        data.find("itemListElement.name"),
+        Some(json!(["Home", "Blog"]))
+    );
+}
+
+#[test]
+fn test_structured_data_itemref_pulls_properties_from_elsewhere() {
+    let html = r#"<div itemscope itemref="extra"><span itemprop="a">1</span></div>
+        <p id="extra"><span itemprop="b">2</span></p>"#;
+    let data = html_to_document(html, &Options::new()).structured_data;
+    assert_eq!(
+        data.microdata,
+        vec![json!({"properties": {"a": ["1"], "b": ["2"]}})]
+    );
+}
+
+#[test]
+fn test_structured_data_merges_selected_properties_into_frontmatter() {
+    let options = Options::new()
+        .with_frontmatter(true)
+        .with_frontmatter_property("title", "headline")
+        .with_frontmatter_property("author", "author.name")
+        .with_frontmatter_property("date", "datePublished")
+        .with_frontmatter_property("breadcrumbs", "itemListElement.name")
+        .with_frontmatter_property("missing", "nope");
+    let md = convert_with(STRUCTURED_DATA_PAGE, &options);
+    assert!(
+        md.starts_with(
+            "---\n\
+             title: Real headline\n\
+             author: Ann Lee\n\
+             date: 2024-05-01\n\
+             breadcrumbs: [Home, Blog]\n\
+             ---\n\n# Pancakes"
+        ),
+        "{md}"
+    );
+}
+
+#[test]
+fn test_structured_data_properties_need_frontmatter_enabled() {
+    let options = Options::new().with_frontmatter_property("title", "headline");
+    assert!(convert_with(STRUCTURED_DATA_PAGE, &options).starts_with("# Pancakes"));
+}
+
+#[test]
+fn test_footnotes_pandoc() {
+    let html = r##"<p>Text<a href="#fn1" class="footnote-ref" id="fnref1" role="doc-noteref"><sup>1</sup></a> and more<a href="#fn2" class="footnote-ref" id="fnref2" role="doc-noteref"><sup>2</sup></a>.</p>
+        <section class="footnotes footnotes-end-of-document" role="doc-endnotes"><hr><ol>
+        <li id="fn1"><p>First note.<a href="#fnref1" class="footnote-back" role="doc-backlink">↩︎</a></p></li>
+        <li id="fn2"><p>Second note.</p><p>More.<a href="#fnref2" class="footnote-back" role="doc-backlink">↩︎</a></p></li>
+        </ol></section>"##;
+    assert_eq!(
+        convert(html),
+        "Text[^1] and more[^2].\n\n[^1]: First note.\n\n[^2]: Second note.\n\n    More.\n"
+    );
+}
+
+#[test]
+fn test_footnotes_wikipedia_numbers_by_first_reference() {
+    let html = r##"<p>A.<sup id="cite_ref-2" class="reference"><a href="#cite_note-2">[2]</a></sup>
+        B.<sup id="cite_ref-1" class="reference"><a href="#cite_note-1">[1]</a></sup>
+        C.<sup id="cite_ref-2b" class="reference"><a href="#cite_note-2">[2]</a></sup></p>
+        <h2>References</h2><div class="reflist"><ol class="references">
+        <li id="cite_note-1"><span class="mw-cite-backlink"><b><a href="#cite_ref-1">^</a></b></span> <span class="reference-text">Jones.</span></li>
+        <li id="cite_note-2"><span class="mw-cite-backlink">^ <a href="#cite_ref-2">a</a> <a href="#cite_ref-2b">b</a></span> <span class="reference-text">Smith, <i>Book</i>.</span></li>
+        </ol></div>"##;
+    assert_eq!(
+        convert(html),
+        "A.[^1] B.[^2] C.[^1]\n\n## References\n\n[^1]: Smith, *Book*.\n\n[^2]: Jones.\n"
+    );
+}
+
+#[test]
+fn test_footnotes_wordpress() {
+    let html = r##"<p>Claim<sup data-fn="abc" class="fn"><a href="#abc" id="abc-link">1</a></sup></p>
+        <ol class="wp-block-footnotes"><li id="abc">Source. <a href="#abc-link" aria-label="Jump to footnote reference 1">↩︎</a></li></ol>"##;
+    assert_eq!(convert(html), "Claim[^1]\n\n[^1]: Source.\n");
+}
+
+#[test]
+fn test_footnotes_sphinx() {
+    let html = r##"<p>Docs<a class="footnote-reference brackets" href="#id2" id="id1" role="doc-noteref"><span class="fn-bracket">[</span>1<span class="fn-bracket">]</span></a>.</p>
+        <aside class="footnote-list brackets"><aside class="footnote brackets" id="id2" role="doc-footnote">
+        <span class="label"><span class="fn-bracket">[</span><a role="doc-backlink" href="#id1">1</a><span class="fn-bracket">]</span></span>
+        <p>Sphinx note.</p></aside></aside>"##;
+    assert_eq!(convert(html), "Docs[^1].\n\n[^1]: Sphinx note.\n");
+}
+
+#[test]
+fn test_footnotes_plain_fragment_links_are_untouched() {
+    let html = r##"<p><a href="#intro">Intro</a> <a href="#missing" class="footnote-ref">x</a></p><h2 id="intro">Intro</h2>"##;
+    assert_eq!(convert(html), "[Intro](#intro) [x](#missing)\n\n## Intro\n");
+}
+
+#[test]
+fn test_footnotes_unreferenced_notes_stay_in_place() {
+    let html = r##"<p>X<a href="#n1" role="doc-noteref">1</a></p>
+        <ol class="footnotes"><li id="n1">One</li><li id="n2">Two</li></ol>"##;
+    assert_eq!(convert(html), "X[^1]\n\n1. Two\n\n[^1]: One\n");
+}
+
+const REFERENCE_LINKS_HTML: &str = r##"<h1>Title</h1>
+    <p>Intro <a href="https://a.test/x">A</a>, <img src="i.png" alt="pic"> and <a href="https://b.test">https://b.test</a>.</p>
+    <h2>One</h2><p>See <a href="https://a.test/x">again</a> and <a href="https://c.test" title="C site">C</a>.</p>
+    <h2>Two</h2><p><a href="#one">Up</a> <a href="https://a.test/x" title="other">A2</a></p>"##;
+
+#[test]
+fn test_reference_links_definitions_at_end_of_document() {
+    let options = Options::new().with_link_style(LinkStyle::Reference);
+    assert_eq!(
+        convert_with(REFERENCE_LINKS_HTML, &options),
+        "# Title\n\n\
+         Intro [A][1], ![pic][2] and <https://b.test>.\n\n\
+         ## One\n\n\
+         See [again][1] and [C][3].\n\n\
+         ## Two\n\n\
+         [Up](#one) [A2][4]\n\n\
+         [1]: https://a.test/x\n\n\
+         [2]: i.png\n\n\
+         [3]: https://c.test \"C site\"\n\n\
+         [4]: https://a.test/x \"other\"\n"
+    );
+}
+
+#[test]
+fn test_reference_links_definitions_at_end_of_each_section() {
+    let options = Options::new()
+        .with_link_style(LinkStyle::Reference)
+        .with_definition_placement(DefinitionPlacement::EndOfSection);
+    assert_eq!(
+        convert_with(REFERENCE_LINKS_HTML, &options),
+        "# Title\n\n\
+         Intro [A][1], ![pic][2] and <https://b.test>.\n\n\
+         [1]: https://a.test/x\n\n\
+         [2]: i.png\n\n\
+         ## One\n\n\
+         See [again][1] and [C][3].\n\n\
+         [3]: https://c.test \"C site\"\n\n\
+         ## Two\n\n\
+         [Up](#one) [A2][4]\n\n\
+         [4]: https://a.test/x \"other\"\n"
+    );
+}
+
+#[test]
+fn test_reference_links_inline_is_default() {
+    let md = convert_with(REFERENCE_LINKS_HTML, &Options::new());
+    assert!(md.contains("[A](https://a.test/x)"), "{md}");
+}
+
+#[test]
+fn test_reference_links_links_in_footnotes_are_defined_before_them() {
+    let html = r##"<p>X<a href="#fn1" role="doc-noteref">1</a></p>
+        <ol class="footnotes"><li id="fn1">See <a href="https://e.test">E</a>.</li></ol>"##;
+    let options = Options::new().with_link_style(LinkStyle::Reference);
+    assert_eq!(
+        convert_with(html, &options),
+        "X[^1]\n\n[1]: https://e.test\n\n[^1]: See [E][1].\n"
+    );
+}
+
+const HEADING_ATTRIBUTES_HTML: &str = r#"<h2 id="setup" class="section main">Setup</h2>
+    <h3><a id="old-style"></a>Legacy</h3>
+    <h3 id="bad id">Spaces</h3>"#;
+
+#[test]
+fn test_heading_attributes_dropped_by_default() {
+    // Upstream behaviour: the empty anchor becomes an empty link.
+    assert_eq!(
+        convert(HEADING_ATTRIBUTES_HTML),
+        "## Setup\n\n### []()Legacy\n\n### Spaces\n"
+    );
+}
+
+#[test]
+fn test_heading_attributes_keeps_ids() {
+    let options = Options::new().with_keep_attributes(KeepAttributes::Id);
+    assert_eq!(
+        convert_with(HEADING_ATTRIBUTES_HTML, &options),
+        "## Setup {#setup}\n\n### Legacy {#old-style}\n\n### Spaces\n"
+    );
+}
+
+#[test]
+fn test_heading_attributes_keeps_ids_and_classes() {
+    let options = Options::new().with_keep_attributes(KeepAttributes::IdAndClasses);
+    assert_eq!(
+        convert_with(
+            r#"<h1 id="a" class="x y">T</h1><h2 class="z">U</h2>"#,
+            &options
+        ),
+        "# T {#a .x .y}\n\n## U {.z}\n"
+    );
+}
+
+#[test]
+fn test_heading_attributes_setext_and_closed_atx() {
+    let html = r#"<h1 id="top">Top</h1>"#;
+    let options = Options::new().with_keep_attributes(KeepAttributes::Id);
+    let setext = options.clone().with_heading_style(HeadingStyle::Setext);
+    assert_eq!(convert_with(html, &setext), "Top {#top}\n==========\n");
+    let mut closed = options;
+    closed.stringify.close_atx = true;
+    assert_eq!(convert_with(html, &closed), "# Top # {#top}\n");
+}
+
+const FRAGMENT_LINKS_HTML: &str = r##"<p><a href="#install">Install</a>, <a href="#install-linux">Linux</a>,
+    <a href="#note">note</a>, <a href="#top">top</a>, <a href="#missing">gone</a></p>
+    <h2 id="install">Install</h2>
+    <h2 id="s2">Usage</h2>
+    <h3 id="install-linux">Install</h3>
+    <p id="note">A note.</p>"##;
+
+#[test]
+fn test_fragment_links_links_point_at_heading_slugs() {
+    // `#top` has no heading before it and is left alone.
+    let html = format!(r#"<p id="top">Intro</p>{FRAGMENT_LINKS_HTML}"#);
+    let options = Options::new().with_rewrite_fragment_links(true);
+    assert_eq!(
+        convert_with(&html, &options),
+        "Intro\n\n\
+         [Install](#install), [Linux](#install-1), [note](#install-1), [top](#top), [gone](#missing)\n\n\
+         ## Install\n\n\
+         ## Usage\n\n\
+         ### Install\n\n\
+         A note.\n"
+    );
+}
+
+#[test]
+fn test_fragment_links_survives_base_url() {
+    let html = format!(r#"<base href="https://x.test/page">{FRAGMENT_LINKS_HTML}"#);
+    let options = Options::new().with_rewrite_fragment_links(true);
+    let md = convert_with(&html, &options);
+    assert!(md.contains("[Linux](#install-1)"), "{md}");
+    assert!(md.contains("[gone](https://x.test/page#missing)"), "{md}");
+}
+
+#[test]
+fn test_fragment_links_kept_ids_take_precedence() {
+    let options = Options::new()
+        .with_rewrite_fragment_links(true)
+        .with_keep_attributes(KeepAttributes::Id);
+    let md = convert_with(FRAGMENT_LINKS_HTML, &options);
+    assert!(md.contains("[Linux](#install-linux)"), "{md}");
+    assert!(md.contains("### Install {#install-linux}"), "{md}");
+}
+
+fn fence(html: &str) -> String {
+    convert(html).lines().next().unwrap_or_default().to_string()
+}
+
+#[test]
+fn test_code_language_highlighter_markup() {
+    assert_eq!(
+        fence(r#"<pre><code class="hljs lang-rust">x</code></pre>"#),
+        "```rust"
+    );
+    assert_eq!(
+        fence(r#"<pre class="language-go"><code>x</code></pre>"#),
+        "```go"
+    );
+    assert_eq!(fence(r#"<pre data-lang="ruby">x</pre>"#), "```ruby");
+    assert_eq!(
+        fence(r#"<pre><code data-language="sql">x</code></pre>"#),
+        "```sql"
+    );
+    assert_eq!(
+        fence(r#"<div class="highlight highlight-source-rust"><pre>x</pre></div>"#),
+        "```rust"
+    );
+    assert_eq!(
+        fence(
+            r#"<div class="highlight-python notranslate"><div class="highlight"><pre>x</pre></div></div>"#
+        ),
+        "```python"
+    );
+    assert_eq!(
+        fence(r#"<div class="language-js codeBlockContainer"><div><pre>x</pre></div></div>"#),
+        "```js"
+    );
+}
+
+#[test]
+fn test_code_language_code_child_wins_over_wrappers() {
+    let html = r#"<div class="highlight-source-c"><pre class="lang-cpp"><code class="language-rust">x</code></pre></div>"#;
+    assert_eq!(fence(html), "```rust");
+}
+
+#[test]
+fn test_code_language_token_classes_inside_pre_are_not_languages() {
+    assert_eq!(
+        fence(r#"<pre class="highlight-line"><code>x</code></pre>"#),
+        "```"
+    );
+}
+
+#[test]
+fn test_code_default_language_aliases() {
+    let html = r#"<pre><code class="language-js">a()</code></pre>
+        <pre class="lang-sh">b</pre><pre data-lang="YML">c: 1</pre>"#;
+    let options = Options::new()
+        .with_language_alias("sh", "sh")
+        .with_default_language_aliases();
+    assert_eq!(
+        convert_with(html, &options),
+        "```javascript\na()\n```\n\n```sh\nb\n```\n\n```yaml\nc: 1\n```\n"
+    );
+    assert!(convert(html).starts_with("```js\n"));
+}
+
+#[test]
+fn test_code_language_aliases() {
+    let options = Options::new()
+        .with_language_alias("js", "javascript")
+        .with_language_alias("nohighlight", "");
+    let html = r#"<pre><code class="language-JS">x</code></pre>
+        <pre><code class="lang-nohighlight">y</code></pre>"#;
+    assert_eq!(
+        convert_with(html, &options),
+        "```javascript\nx\n```\n\n```\ny\n```\n"
+    );
+}
+
+const LANGUAGE_GUESSING_HTML: &str = "<pre>#!/bin/sh\necho hi</pre>\
+    <pre>x = 1</pre>\
+    <pre class=\"language-text\">def f():\n    pass</pre>";
+
+#[test]
+fn test_language_guessing_off_by_default() {
+    assert!(convert(LANGUAGE_GUESSING_HTML).starts_with("```\n#!/bin/sh"));
+}
+
+#[test]
+fn test_language_guessing_confident_guesses_only() {
+    let options = Options::new().with_language_guessing(0.6);
+    assert_eq!(
+        convert_with(LANGUAGE_GUESSING_HTML, &options),
+        "```bash\n#!/bin/sh\necho hi\n```\n\n```\nx = 1\n```\n\n```text\ndef f():\n    pass\n```\n"
+    );
+}
+
+#[test]
+fn test_language_guessing_respects_aliases() {
+    let options = Options::new()
+        .with_language_guessing(0.6)
+        .with_language_alias("bash", "sh")
+        .with_language_alias("nohighlight", "");
+    assert_eq!(
+        convert_with(
+            "<pre>#!/bin/sh\necho hi</pre>\
+             <pre class=\"lang-nohighlight\">#!/bin/sh\necho hi</pre>",
+            &options
+        ),
+        "```sh\n#!/bin/sh\necho hi\n```\n\n```\n#!/bin/sh\necho hi\n```\n"
+    );
+}
+
+#[test]
+#[should_panic(expected = "between 0 and 1")]
+fn test_language_guessing_threshold_out_of_range_panics() {
+    let _ = Options::new().with_language_guessing(1.5);
+}
+
+#[test]
+fn test_code_cleanup_pygments_table_gutter() {
+    let html = r#"<div class="highlight-python notranslate"><div class="highlight">
+        <table class="highlighttable"><tr><td class="linenos"><div class="linenodiv"><pre>1
+2</pre></div></td><td class="code"><div class="highlight"><pre>import os
+print(os)
+</pre></div></td></tr></table></div></div>"#;
+    let options = Options::new().with_code_strip_chrome(true);
+    assert_eq!(
+        convert_with(html, &options),
+        "```python\nimport os\nprint(os)\n```\n"
+    );
+}
+
+#[test]
+fn test_code_cleanup_numeric_gutter_cell() {
+    let html = "<table><tr><td><pre>1\n2</pre></td><td><pre>a\nb</pre></td></tr></table>";
+    let options = Options::new().with_code_strip_chrome(true);
+    assert_eq!(convert_with(html, &options), "```\na\nb\n```\n");
+    assert!(convert(html).starts_with("|"));
+}
+
+#[test]
+fn test_code_cleanup_inline_line_numbers_and_copy_button() {
+    let html = r#"<div class="highlight"><pre><span class="linenos">1</span>a = 1
+<span class="linenos">2</span>b = 2
+<button class="copy-button">Copy</button></pre></div><button>Copied!</button>"#;
+    let options = Options::new().with_code_strip_chrome(true);
+    assert_eq!(convert_with(html, &options), "```\na = 1\nb = 2\n```\n");
+}
+
+#[test]
+fn test_code_cleanup_highlightjs_line_numbers() {
+    let row = |n: u8, code: &str| {
+        format!(
+            r#"<tr><td class="hljs-ln-numbers"><div class="hljs-ln-n">{n}</div></td><td class="hljs-ln-code"><div>{code}</div></td></tr>"#
+        )
+    };
+    let html = format!(
+        r#"<pre><code class="language-js"><table class="hljs-ln"><tbody>{}{}</tbody></table></code></pre>"#,
+        row(1, "x"),
+        row(2, "y")
+    );
+    let options = Options::new().with_code_strip_chrome(true);
+    assert_eq!(convert_with(&html, &options), "```js\nx\ny\n```\n");
+}
+
+#[test]
+fn test_code_cleanup_off_by_default() {
+    let html = r#"<div><pre><span class="linenos">1</span>a</pre><button>Copy</button></div>"#;
+    assert_eq!(convert(html), "```\n1a\n```\n\nCopy\n");
+}
+
+#[test]
+fn test_code_cleanup_generic_classes_are_kept() {
+    let options = Options::new().with_code_strip_chrome(true);
+    assert_eq!(
+        convert_with(
+            r#"<pre><span class="line-number">note</span> y <span class="gutter">z</span></pre>"#,
+            &options
+        ),
+        "```\nnote y z\n```\n"
+    );
+}
+
+#[test]
+fn test_code_cleanup_other_buttons_are_kept() {
+    assert_eq!(convert("<p><button>Submit</button></p>"), "Submit\n");
+}
+
+#[test]
+fn test_code_cleanup_copy_classes_away_from_code_are_kept() {
+    assert_eq!(
+        convert(r#"<p class="copy">Copyright 2024 ACME</p>"#),
+        "Copyright 2024 ACME\n"
+    );
+    assert_eq!(
+        convert(r#"<div class="clipboard"><p>Copying files is easy.</p></div>"#),
+        "Copying files is easy.\n"
+    );
+    assert_eq!(
+        convert("<p>Press <button>Copy</button> to copy.</p>"),
+        "Press Copy to copy.\n"
+    );
+    assert_eq!(
+        convert_with(
+            "<pre>x</pre><p class=\"copy\">Copyright 2024</p>",
+            &Options::new().with_code_strip_chrome(true)
+        ),
+        "```\nx\n```\n\nCopyright 2024\n"
+    );
+}
+
+#[test]
+fn test_code_cleanup_dedent() {
+    let html = "<pre>    fn a() {\n        b();\n    }</pre>";
+    assert_eq!(
+        convert(html),
+        "```\n    fn a() {\n        b();\n    }\n```\n"
+    );
+    let options = Options::new().with_code_dedent(true);
+    assert_eq!(
+        convert_with(html, &options),
+        "```\nfn a() {\n    b();\n}\n```\n"
+    );
+}
+
+#[test]
+fn test_code_cleanup_prompts_move_to_meta() {
+    let html = r#"<pre><code class="language-shell">$ cargo build
+   Compiling demo
+$ ./demo</code></pre>"#;
+    let options = Options::new().with_code_strip_prompts(true);
+    assert_eq!(
+        convert_with(html, &options),
+        "```shell prompt=\"$\"\ncargo build\n   Compiling demo\n./demo\n```\n"
+    );
+    assert!(convert(html).contains("$ cargo build"));
+    assert_eq!(
+        convert_with("<pre><code>$ echo hi\n$ ls</code></pre>", &options),
+        "```console prompt=\"$\"\necho hi\nls\n```\n"
+    );
+    assert_eq!(
+        convert_with("<pre>>>> 1 + 1\n2</pre>", &options),
+        "```pycon prompt=\">>>\"\n1 + 1\n2\n```\n"
+    );
+}
+
+#[test]
+fn test_code_cleanup_prompts_keep_quoted_output() {
+    let options = Options::new().with_code_strip_prompts(true);
+    assert_eq!(
+        convert_with(
+            "<pre>$ cat reply.txt\n> quoted line\n$ echo a \\\n> b</pre>",
+            &options
+        ),
+        "```console prompt=\"$\"\ncat reply.txt\n> quoted line\necho a \\\nb\n```\n"
+    );
+}
+
+#[test]
+fn test_code_meta_attributes() {
+    let html = r#"<pre data-line="3-5" data-title="main.rs"><code class="language-rust">fn main() {}</code></pre>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_code_meta(true)),
+        "```rust title=\"main.rs\" {3-5}\nfn main() {}\n```\n"
+    );
+    assert_eq!(convert(html), "```rust\nfn main() {}\n```\n");
+}
+
+#[test]
+fn test_code_meta_needs_language() {
+    let options = Options::new().with_code_meta(true);
+    assert_eq!(
+        convert_with(r#"<pre data-title="main.rs">x</pre>"#, &options),
+        "```\nx\n```\n"
+    );
+    let html = r#"<figure><figcaption>notes.txt</figcaption><pre>x</pre></figure>"#;
+    assert_eq!(convert_with(html, &options), "notes.txt\n\n```\nx\n```\n");
+}
+
+#[test]
+fn test_code_meta_backtick_switches_fence() {
+    let html = r#"<pre data-title="a`b"><code class="language-rust">x</code></pre>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_code_meta(true)),
+        "~~~rust title=\"a`b\"\nx\n~~~\n"
+    );
+}
+
+#[test]
+fn test_code_meta_figcaption() {
+    let html = r#"<figure><figcaption>src/lib.rs</figcaption><pre><code class="language-rust">x</code></pre></figure>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_code_meta(true)),
+        "```rust title=\"src/lib.rs\"\nx\n```\n"
+    );
+    assert_eq!(convert(html), "src/lib.rs\n\n```rust\nx\n```\n");
+}
+
+#[test]
+fn test_code_meta_wrapper_headers() {
+    let mkdocs = r#"<div class="language-python highlight"><span class="filename">app.py</span><pre><code>x</code></pre></div>"#;
+    assert_eq!(
+        convert_with(mkdocs, &Options::new().with_code_meta(true)),
+        "```python title=\"app.py\"\nx\n```\n"
+    );
+    let sphinx = r##"<div class="literal-block-wrapper"><div class="code-block-caption"><span class="caption-text">conf.py</span><a class="headerlink" href="#c">¶</a></div>
+        <div class="highlight-python notranslate"><div class="highlight"><pre>x</pre></div></div></div>"##;
+    assert_eq!(
+        convert_with(sphinx, &Options::new().with_code_meta(true)),
+        "```python title=\"conf.py\"\nx\n```\n"
+    );
+}
+
+#[test]
+fn test_code_meta_custom_mapping_and_prompts() {
+    let html = r#"<pre data-highlight="2" data-title='say "hi"'><code class="language-shell">$ echo hi</code></pre>"#;
+    let options = Options::new()
+        .with_code_meta(true)
+        .with_code_meta_attribute("data-highlight", "hl_lines=\"{}\"")
+        .with_code_strip_prompts(true);
+    assert_eq!(
+        convert_with(html, &options),
+        "```shell title=\"say \\\"hi\\\"\" hl_lines=\"2\" prompt=\"$\"\necho hi\n```\n"
+    );
+}
+
+const LOSSY: &str = r#"<table class="x" style="width:100%">
+  <caption>Revenue</caption>
+  <thead><tr><th rowspan="2" class="a">Region</th><th colspan="2">2024</th></tr>
+  <tr><th>Q1</th><th>Q2</th></tr></thead>
+  <tbody><tr><td><a href="/eu" class="l">EU</a></td><td align="right">1 &lt; 2</td><td><pre>a
+
+b</pre></td></tr></tbody>
+</table>"#;
+
+#[test]
+fn test_table_fallback_losses_are_reported() {
+    let html = format!("{LOSSY}<table><tr><td>a</td></tr></table>");
+    assert_eq!(
+        table_losses(&html, &Options::new()),
+        vec![
+            vec![
+                TableLoss::ColSpan,
+                TableLoss::RowSpan,
+                TableLoss::BlockContent,
+                TableLoss::MultipleHeaderRows
+            ],
+            vec![]
+        ]
+    );
+    assert_eq!(
+        table_losses(
+            "<table><tr><td><table><tr><td>x</td></tr></table></td></tr></table>",
+            &Options::new()
+        ),
+        vec![vec![TableLoss::NestedTable]]
+    );
+    assert_eq!(
+        table_losses(
+            "<table><tr><td><p>a</p><p>b</p></td></tr></table>",
+            &Options::new()
+        ),
+        vec![vec![TableLoss::BlockContent]]
+    );
+}
+
+#[test]
+fn test_table_fallback_losses_respect_selectors() {
+    let html = format!(r#"<aside>{LOSSY}</aside><main><table><tr><td>a</td></tr></table></main>"#);
+    assert_eq!(
+        table_losses(&html, &Options::new().with_root_selector("main")),
+        vec![vec![]]
+    );
+    assert_eq!(
+        table_losses(&html, &Options::new().with_exclude_selector("aside")),
+        vec![vec![]]
+    );
+}
+
+#[test]
+fn test_table_fallback_lossy_table_kept_as_clean_html() {
+    let html = format!(r#"<base href="https://x.test/"><p>Before</p>{LOSSY}<p>After</p>"#);
+    assert_eq!(
+        convert_with(
+            &html,
+            &Options::new().with_table_strategy(TableStrategy::HtmlFallback)
+        ),
+        "Before\n\n\
+         <table>\n\
+         <caption>Revenue</caption>\n\
+         <thead>\n\
+         <tr>\n\
+         <th rowspan=\"2\">Region</th>\n\
+         <th colspan=\"2\">2024</th>\n\
+         </tr>\n\
+         <tr>\n\
+         <th>Q1</th>\n\
+         <th>Q2</th>\n\
+         </tr>\n\
+         </thead>\n\
+         <tbody>\n\
+         <tr>\n\
+         <td><a href=\"https://x.test/eu\">EU</a></td>\n\
+         <td>1 &lt; 2</td>\n\
+         <td><pre>a&#10;&#10;b</pre></td>\n\
+         </tr>\n\
+         </tbody>\n\
+         </table>\n\n\
+         After\n"
+    );
+}
+
+#[test]
+fn test_table_fallback_keeps_only_table_and_phrasing_markup() {
+    let html = r#"<table><tr><td colspan="2"><div><p>a</p><p><a href="javascript:alert(1)">b</a></p></div></td></tr>
+        <tr><td><iframe src="https://x.test/"></iframe><img src=" DATA:image/png;base64,AA" alt="i"></td><td><form><em>c</em></form></td></tr></table>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_table_strategy(TableStrategy::HtmlFallback)
+        ),
+        "<table>\n\
+         <tbody>\n\
+         <tr>\n\
+         <td colspan=\"2\">a <a>b</a></td>\n\
+         </tr>\n\
+         <tr>\n\
+         <td><img alt=\"i\"></td>\n\
+         <td><em>c</em></td>\n\
+         </tr>\n\
+         </tbody>\n\
+         </table>\n"
+    );
+}
+
+#[test]
+fn test_table_fallback_pre_whitespace_lines_stay_in_block() {
+    let html =
+        "<table><tr><td colspan=\"2\"><pre>a\n   \nb<span>\n</span>\nc</pre></td></tr></table>";
+    let md = convert_with(
+        html,
+        &Options::new().with_table_strategy(TableStrategy::HtmlFallback),
+    );
+    assert!(
+        md.contains("<pre>a&#10;   &#10;b<span>&#10;</span>&#10;c</pre>"),
+        "{md:?}"
+    );
+    assert!(!md.lines().any(|line| line.trim().is_empty()), "{md:?}");
+}
+
+#[test]
+fn test_table_fallback_lossless_tables_stay_gfm() {
+    let html = "<table><tr><th>A</th></tr><tr><td>1</td></tr></table>";
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_table_strategy(TableStrategy::HtmlFallback)
+        ),
+        "| A |\n| - |\n| 1 |\n"
+    );
+}
+
+const TABLE: &str = "<table>\
+    <tr><th>Name</th><th align=\"right\">Qty</th></tr>\
+    <tr><td>Apple</td><td>3</td></tr>\
+    <tr><td>日本</td><td></td></tr>\
+    </table>";
+
+#[test]
+fn test_table_styles_compact() {
+    assert_eq!(
+        convert_with(TABLE, &Options::new().with_table_style(TableStyle::Compact)),
+        "|Name|Qty|\n|-|-:|\n|Apple|3|\n|日本||\n"
+    );
+}
+
+#[test]
+fn test_table_styles_key_value() {
+    assert_eq!(
+        convert_with(
+            TABLE,
+            &Options::new().with_table_style(TableStyle::KeyValue)
+        ),
+        "* Name: Apple\n* Qty: 3\n\n- Name: 日本\n"
+    );
+}
+
+#[test]
+fn test_table_styles_key_value_header_only() {
+    let html = "<table><tr><th>Name</th><th></th><th>Qty</th></tr></table>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_table_style(TableStyle::KeyValue)),
+        "* Name\n* Qty\n"
+    );
+}
+
+#[test]
+fn test_table_styles_key_value_names_headerless_columns() {
+    let html = "<table><tr><td>a</td><td>b</td></tr></table>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_table_style(TableStyle::KeyValue)),
+        "* Column 1: a\n* Column 2: b\n"
+    );
+}
+
+#[test]
+fn test_table_styles_grid_measures_wide_characters() {
+    assert_eq!(
+        convert_with(TABLE, &Options::new().with_table_style(TableStyle::Grid)),
+        "+-------+-----+\n\
+         | Name  | Qty |\n\
+         +=======+=====+\n\
+         | Apple |   3 |\n\
+         +-------+-----+\n\
+         | 日本  |     |\n\
+         +-------+-----+\n"
+    );
+}
+
+#[test]
+fn test_table_cell_breaks_keeps_line_structure() {
+    let html = "<table>\
+        <tr><th>A</th><th>B</th></tr>\
+        <tr><td>line1<br>line2</td><td><p>Para one</p><p>Para two</p></td></tr>\
+        <tr><td><ul><li>x</li><li>y <b>z</b></li></ul></td>\
+        <td><div>d1</div><div>d2</div></td></tr>\
+        </table>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_table_cell_breaks(true)),
+        "| A                | B                    |\n\
+         | ---------------- | -------------------- |\n\
+         | line1<br>line2   | Para one<br>Para two |\n\
+         | • x<br>• y **z** | d1<br>d2             |\n"
+    );
+}
+
+#[test]
+fn test_table_cell_breaks_ordered_lists_keep_numbers() {
+    let html = "<table><tr><th>Steps</th></tr>\
+        <tr><td><ol start=\"2\"><li>one</li><li>two</li></ol></td></tr></table>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_table_cell_breaks(true)),
+        "| Steps            |\n| ---------------- |\n| 2. one<br>3. two |\n"
+    );
+}
+
+#[test]
+fn test_table_cell_breaks_in_hand_built_trees() {
+    let text = |value: &str| {
+        Node::Text(mdast::Text {
+            value: value.to_string(),
+        })
+    };
+    let row = |children| Node::TableRow(mdast::TableRow { children });
+    let tree = Node::Root(mdast::Root {
+        children: vec![Node::Table(mdast::Table {
+            align: vec![None],
+            children: vec![
+                row(vec![Node::TableCell(mdast::TableCell::new(vec![text(
+                    "A",
+                )]))]),
+                row(vec![Node::TableCell(mdast::TableCell::new(vec![
+                    text("a"),
+                    Node::Break(mdast::Break),
+                    text("b"),
+                ]))]),
+            ],
+        })],
+    });
+    let options = StringifyOptions {
+        table_cell_breaks: true,
+        ..StringifyOptions::default()
+    };
+    assert_eq!(
+        html2markdown::mdast_to_string(&tree, &options),
+        "| A      |\n| ------ |\n| a<br>b |\n"
+    );
+    assert_eq!(
+        html2markdown::mdast_to_string(&tree, &StringifyOptions::default()),
+        "| A   |\n| --- |\n| a b |\n"
+    );
+}
+
+#[test]
+fn test_table_cell_breaks_off_by_default() {
+    let html = "<table><tr><th>A</th></tr><tr><td>a<br>b</td></tr></table>";
+    assert_eq!(
+        convert_with(html, &Options::new()),
+        "| A   |\n| --- |\n| a b |\n"
+    );
+}
+
+const SPANS: &str = "<table>\
+    <thead><tr><th>Region</th><th>Q1</th><th>Q2</th></tr></thead>\
+    <tbody>\
+    <tr><td rowspan=\"2\">EU</td><td colspan=\"2\">flat</td></tr>\
+    <tr><td>1</td><td>2</td></tr>\
+    </tbody></table>";
+
+fn strings(row: &[&str]) -> Vec<String> {
+    row.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_table_extraction_tables_as_grids() {
+    let html = "<p>Intro</p>\
+        <table><caption> Prices </caption>\
+        <tr><th>Item</th><th align=\"right\">Cost</th></tr>\
+        <tr><td><a href=\"/a\">Apple</a> <img alt=\"(red)\"></td><td>1<br>2</td></tr>\
+        </table>\
+        <table><tr><td>x</td><td><table><tr><td>nested</td></tr></table></td></tr></table>";
+    assert_eq!(
+        extract_tables(html, &Options::new()),
+        vec![
+            TableData {
+                caption: Some("Prices".to_string()),
+                header: Some(strings(&["Item", "Cost"])),
+                rows: vec![strings(&["Apple (red)", "1\n2"])],
+                align: vec![None, Some(AlignKind::Right)],
+            },
+            TableData {
+                caption: None,
+                header: None,
+                rows: vec![strings(&["x", "nested"])],
+                align: vec![None, None],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_table_extraction_spans_empty_by_default() {
+    let tables = extract_tables(SPANS, &Options::new());
+    assert_eq!(
+        tables[0].rows,
+        vec![strings(&["EU", "flat", ""]), strings(&["", "1", "2"])]
+    );
+}
+
+#[test]
+fn test_table_extraction_spans_duplicated_on_request() {
+    let options = Options::new().with_table_duplicate_spans(true);
+    let tables = extract_tables(SPANS, &options);
+    assert_eq!(
+        tables[0].rows,
+        vec![strings(&["EU", "flat", "flat"]), strings(&["EU", "1", "2"])]
+    );
+    assert_eq!(
+        convert_with(SPANS, &options),
+        "| Region | Q1   | Q2   |\n\
+         | ------ | ---- | ---- |\n\
+         | EU     | flat | flat |\n\
+         | EU     | 1    | 2    |\n"
+    );
+}
+
+#[test]
+fn test_table_extraction_csv_and_json_lines() {
+    let tables = extract_tables(SPANS, &Options::new());
+    assert_eq!(tables[0].to_csv(), "Region,Q1,Q2\r\nEU,flat,\r\n,1,2\r\n");
+    assert_eq!(
+        tables[0].to_json_lines(),
+        "{\"Region\":\"EU\",\"Q1\":\"flat\",\"Q2\":\"\"}\n\
+         {\"Region\":\"\",\"Q1\":\"1\",\"Q2\":\"2\"}\n"
+    );
+    let headless = extract_tables(
+        "<table><tr><td>a</td><td>\"b\"</td></tr></table>",
+        &Options::new(),
+    );
+    assert_eq!(headless[0].to_json_lines(), "[\"a\",\"\\\"b\\\"\"]\n");
+}
+
+#[test]
+fn test_table_extraction_respects_root_selector_and_fallback() {
+    let html = "<table><tr><td>skip</td></tr></table>\
+        <main><table><tr><td colspan=\"2\">kept</td></tr></table></main>";
+    let options = Options::new()
+        .with_root_selector("main")
+        .with_table_strategy(html2markdown::TableStrategy::HtmlFallback);
+    let tables = extract_tables(html, &options);
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].rows, vec![strings(&["kept", ""])]);
+}
+
+const CAPTIONED: &str = "<table><caption>Table 1: <b>Prices</b></caption>\
+    <tr><th>A</th></tr><tr><td>1</td></tr></table>";
+
+#[test]
+fn test_table_captions_dropped_by_default() {
+    assert_eq!(convert(CAPTIONED), "| A |\n| - |\n| 1 |\n");
+}
+
+#[test]
+fn test_table_captions_paragraph_before_table() {
+    let options = Options::new().with_table_caption(TableCaption::Paragraph);
+    assert_eq!(
+        convert_with(CAPTIONED, &options),
+        "Table 1: **Prices**\n\n| A |\n| - |\n| 1 |\n"
+    );
+}
+
+#[test]
+fn test_table_captions_emphasized() {
+    let options = Options::new().with_table_caption(TableCaption::Emphasis);
+    assert_eq!(
+        convert_with(CAPTIONED, &options),
+        "*Table 1: **Prices***\n\n| A |\n| - |\n| 1 |\n"
+    );
+}
+
+#[test]
+fn test_table_captions_bold_first_row_promoted() {
+    let html = "<table>\
+        <tr><td><b>Name</b></td><td><strong>Qty</strong> </td><td></td></tr>\
+        <tr><td>Apple</td><td>3</td><td>x</td></tr></table>";
+    let options = Options::new().with_table_promote_header(true);
+    assert_eq!(
+        convert_with(html, &options),
+        "| Name  | Qty |   |\n| ----- | --- | - |\n| Apple | 3   | x |\n"
+    );
+    assert_eq!(
+        convert(html),
+        "|          |         |   |\n\
+         | -------- | ------- | - |\n\
+         | **Name** | **Qty** |   |\n\
+         | Apple    | 3       | x |\n"
+    );
+}
+
+#[test]
+fn test_table_captions_mixed_first_row_not_promoted() {
+    let html = "<table>\
+        <tr><td><b>Name:</b> Ann</td></tr><tr><td>Bob</td></tr></table>";
+    let options = Options::new().with_table_promote_header(true);
+    assert_eq!(
+        convert_with(html, &options),
+        "|               |\n| ------------- |\n| **Name:** Ann |\n| Bob           |\n"
+    );
+}
+
+#[test]
+fn test_layout_tables_presentation_role_unwrapped() {
+    let html = r#"<table role="presentation"><tr>
+        <td><h1>Newsletter</h1><p>Hello <b>there</b>.</p></td>
+        <td>Sidebar text</td>
+        </tr></table>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_unwrap_layout_tables(true)),
+        "# Newsletter\n\nHello **there**.\n\nSidebar text\n"
+    );
+}
+
+#[test]
+fn test_layout_tables_nested_layout_tables_unwrapped() {
+    let html = "<table><tr><td>\
+        <table><tr><td><p>One</p><p>Two</p></td></tr></table>\
+        </td></tr><tr><td>Footer</td></tr></table>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_unwrap_layout_tables(true)),
+        "One\n\nTwo\n\nFooter\n"
+    );
+}
+
+#[test]
+fn test_layout_tables_data_tables_kept() {
+    let html = "<table><tr><th>A</th></tr><tr><td><p>1</p><p>2</p></td></tr></table>\
+        <table><tr><td>a</td><td>b</td></tr></table>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_unwrap_layout_tables(true)),
+        "| A  |\n| -- |\n| 12 |\n\n|   |   |\n| - | - |\n| a | b |\n"
+    );
+}
+
+#[test]
+fn test_layout_tables_off_by_default() {
+    let html = r#"<table role="presentation"><tr><td>x</td></tr></table>"#;
+    assert_eq!(convert_with(html, &Options::new()), "|   |\n| - |\n| x |\n");
+}
+
+#[test]
+fn test_keep_html_inline_elements_kept() {
+    let html = r#"<p>E = mc<sup>2</sup>, H<sub>2</sub>O, press <kbd>Ctrl</kbd>+<kbd>C</kbd>, <abbr title="HyperText &quot;Markup&quot;">HTML</abbr> and <u><b>this</b></u>.</p>"#;
+    let options = Options::new()
+        .with_keep_html("sup")
+        .with_keep_html("sub")
+        .with_keep_html("kbd")
+        .with_keep_html("abbr")
+        .with_keep_html("U");
+    assert_eq!(
+        convert_with(html, &options),
+        "E = mc<sup>2</sup>, H<sub>2</sub>O, press <kbd>Ctrl</kbd>+<kbd>C</kbd>, \
+         <abbr title=\"HyperText &quot;Markup&quot;\">HTML</abbr> and <u>**this**</u>.\n"
+    );
+    assert_eq!(
+        convert(html),
+        "E = mc2, H2O, press `Ctrl`+`C`, HTML and ***this***.\n"
+    );
+}
+
+#[test]
+fn test_keep_html_details_keeps_markdown_body() {
+    let html = "<details open><summary>More <b>info</b></summary>\
+        <p>Some <em>text</em>.</p><ul><li>item</li></ul></details>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_keep_html("details")),
+        "<details open=\"\">\n<summary>More info</summary>\n\n\
+         Some *text*.\n\n* item\n\n</details>\n"
+    );
+}
+
+#[test]
+fn test_keep_html_inline_element_at_top_level_joins_paragraph() {
+    assert_eq!(
+        convert_with(
+            "x<mark>y</mark>z<div>next</div>",
+            &Options::new().with_keep_html("mark")
+        ),
+        "x<mark>y</mark>z\n\nnext\n"
+    );
+}
+
+#[test]
+#[should_panic(expected = "cannot keep <a> as HTML")]
+fn test_keep_html_unsupported_tag_panics_in_builder() {
+    let _ = Options::new().with_keep_html("a");
+}
+
+#[test]
+fn test_keep_html_field_is_normalized() {
+    let mut options = Options::new();
+    options.keep_html = vec!["SUP".into(), "a".into(), "script".into()];
+    assert_eq!(
+        convert_with(
+            r#"<p>x<sup>2</sup> <a href="/y">y</a><script>z()</script></p>"#,
+            &options
+        ),
+        "x<sup>2</sup> [y](/y)\n"
+    );
+}
+
+const INLINE_EXTENSIONS_HTML: &str = "<p>A <mark>key point</mark>, x<sup>2</sup>, H<sub>2</sub>O, \
+    <ins>added</ins> and <u>underlined</u> text.</p>";
+
+#[test]
+fn test_inline_extensions_fallback_is_unchanged() {
+    assert_eq!(
+        convert(INLINE_EXTENSIONS_HTML),
+        "A *key point*, x2, H2O, added and *underlined* text.\n"
+    );
+}
+
+#[test]
+fn test_inline_extensions_extension_syntax() {
+    assert_eq!(
+        convert_with(
+            INLINE_EXTENSIONS_HTML,
+            &Options::new()
+                .with_highlight(InlineSyntax::Extension)
+                .with_superscript(InlineSyntax::Extension)
+                .with_subscript(InlineSyntax::Extension)
+                .with_insert(InlineSyntax::Extension)
+        ),
+        "A ==key point==, x^2^, H~2~O, ++added++ and ++underlined++ text.\n"
+    );
+}
+
+#[test]
+fn test_inline_extensions_raw_html() {
+    assert_eq!(
+        convert_with(
+            INLINE_EXTENSIONS_HTML,
+            &Options::new()
+                .with_highlight(InlineSyntax::Html)
+                .with_superscript(InlineSyntax::Html)
+                .with_subscript(InlineSyntax::Html)
+                .with_insert(InlineSyntax::Html)
+        ),
+        "A <mark>key point</mark>, x<sup>2</sup>, H<sub>2</sub>O, \
+         <ins>added</ins> and <ins>underlined</ins> text.\n"
+    );
+}
+
+#[test]
+fn test_inline_extensions_per_construct_choice() {
+    let options = Options::new()
+        .with_superscript(InlineSyntax::Extension)
+        .with_highlight(InlineSyntax::Html);
+    assert_eq!(
+        convert_with(INLINE_EXTENSIONS_HTML, &options),
+        "A <mark>key point</mark>, x^2^, H2O, added and *underlined* text.\n"
+    );
+}
+
+#[test]
+fn test_inline_extensions_edge_whitespace_moved_outside_delimiters() {
+    let options = Options::new().with_insert(InlineSyntax::Extension);
+    assert_eq!(
+        convert_with("<p>an <u>underlined </u>word</p>", &options),
+        "an ++underlined++ word\n"
+    );
+}
+
+#[test]
+fn test_inline_extensions_spaces_escaped_in_superscript() {
+    let options = Options::new().with_superscript(InlineSyntax::Extension);
+    assert_eq!(
+        convert_with("<p>1<sup>st place</sup></p>", &options),
+        "1^st\\ place^\n"
+    );
+    assert_eq!(
+        convert_with("<p>x<sup><code>a b</code></sup></p>", &options),
+        "x^`a b`^\n"
+    );
+    assert_eq!(
+        convert_with(
+            r#"<p>x<sup><a href="/a" title="t t">a b</a></sup></p>"#,
+            &options
+        ),
+        "x^[a\\ b](/a \"t t\")^\n"
+    );
+}
+
+#[test]
+fn test_inline_extensions_footnote_marks_not_superscripted() {
+    let html = r##"<p>Claim<sup class="reference"><a href="#cite_note-1">[1]</a></sup></p>
+        <ol class="references"><li id="cite_note-1"><span class="reference-text">Source.</span></li></ol>"##;
+    let options = Options::new().with_superscript(InlineSyntax::Extension);
+    assert_eq!(convert_with(html, &options), convert(html));
+    assert!(convert(html).starts_with("Claim[^1]"));
+}
+
+#[test]
+fn test_inline_extensions_delimiters_in_text_escaped_when_enabled() {
+    let html = "<p>2^10 a==b c++ ~home</p>";
+    assert_eq!(convert(html), "2^10 a==b c++ ~home\n");
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new()
+                .with_highlight(InlineSyntax::Extension)
+                .with_superscript(InlineSyntax::Extension)
+                .with_subscript(InlineSyntax::Extension)
+                .with_insert(InlineSyntax::Extension)
+        ),
+        "2\\^10 a\\==b c\\++ \\~home\n"
+    );
+}
+
+#[test]
+fn test_math_mathml_tex_annotation() {
+    let html = r#"<p>Euler: <math><semantics><mrow><msup><mi>e</mi><mrow><mi>i</mi><mi>π</mi></mrow></msup></mrow><annotation encoding="application/x-tex">e^{i\pi}</annotation></semantics></math> = −1</p>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_math(true)),
+        "Euler: $e^{i\\pi}$ = −1\n"
+    );
+    assert!(!convert(html).contains('$'));
+}
+
+#[test]
+fn test_math_katex_inline_and_display() {
+    let html = r#"<p>Inline <span class="katex"><span class="katex-mathml"><math><semantics><mrow><mi>x</mi></mrow><annotation encoding="application/x-tex">x^2</annotation></semantics></math></span><span class="katex-html" aria-hidden="true"><span class="base">x2</span></span></span> here.</p>
+<span class="katex-display"><span class="katex"><span class="katex-mathml"><math display="block"><semantics><mrow></mrow><annotation encoding="application/x-tex">\int_0^1 f(x)\,dx</annotation></semantics></math></span><span class="katex-html">rendered</span></span></span>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_math(true)),
+        "Inline $x^2$ here.\n\n$$\n\\int_0^1 f(x)\\,dx\n$$\n"
+    );
+}
+
+#[test]
+fn test_math_mathjax_scripts() {
+    let html = r#"<p>Mass <span class="MathJax_Preview">E=mc2</span><span class="MathJax">E=mc2</span><script type="math/tex">E=mc^2</script>.</p>
+<div><script type="math/tex; mode=display">a^2 + b^2 = c^2</script></div>"#;
+    assert_eq!(
+        convert_with(html, &Options::new().with_math(true)),
+        "Mass $E=mc^2$.\n\n$$\na^2 + b^2 = c^2\n$$\n"
+    );
+}
+
+#[test]
+fn test_math_mathml_without_annotation() {
+    let html = "<math display=\"block\"><mi>a</mi><mo>=</mo>\
+        <msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>+</mo><msup><mi>c</mi><mn>2</mn></msup></msqrt>\
+        <mo>≤</mo><mfrac><mi>α</mi><mrow><mi>sin</mi><mi>x</mi></mrow></mfrac></math>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_math(true)),
+        "$$\na=\\sqrt{b^2+c^2}\\leq\\frac{\\alpha}{\\sin x}\n$$\n"
+    );
+}
+
+#[test]
+fn test_math_parenthesized_tex_in_text() {
+    let html = "<p>Let \\(x \\in \\mathbb{R}\\) cost $5.</p><pre>\\(kept\\)</pre>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_math(true)),
+        "Let $x \\in \\mathbb{R}$ cost \\$5.\n\n```\n\\(kept\\)\n```\n"
+    );
+}
+
+#[test]
+fn test_math_display_in_phrasing_is_inline() {
+    let options = Options::new().with_math(true);
+    let html = r#"<p>Euler: <math display="block"><mi>x</mi></math> holds.</p>
+<p><math display="block"><mi>y</mi></math></p>
+<table><tr><th>Formula</th></tr><tr><td><math display="block"><mi>z</mi></math></td></tr></table>"#;
+    assert_eq!(
+        convert_with(html, &options),
+        "Euler: $x$ holds.\n\n$$\ny\n$$\n\n| Formula |\n| ------- |\n| $z$     |\n"
+    );
+}
+
+#[test]
+fn test_math_dollar_in_value_is_escaped() {
+    let options = Options::new().with_math(true);
+    assert_eq!(
+        convert_with("<p><math><mi>a</mi><mo>$</mo></math></p>", &options),
+        "$a\\$$\n"
+    );
+    let tex = r#"<p><math><semantics><mi>a</mi><annotation encoding="application/x-tex">a$</annotation></semantics></math></p>"#;
+    assert_eq!(convert_with(tex, &options), "$a\\$$\n");
+}
+
+#[test]
+fn test_definition_lists_terms_and_descriptions() {
+    let html = "<dl><dt>Apple</dt><dd>A <em>red</em> fruit.</dd>\
+        <dt>Pear</dt><dt>Quince</dt><dd>Green.</dd><dd>Yellow.</dd></dl>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_definition_lists(true)),
+        "Apple\n: A *red* fruit.\n\nPear\n: Green.\n: Yellow.\n\nQuince\n: Green.\n: Yellow.\n"
+    );
+}
+
+#[test]
+fn test_definition_lists_list_fallback_by_default() {
+    let html = "<dl><dt>Apple</dt><dd>A red fruit.</dd></dl>";
+    assert_eq!(convert(html), "* Apple\n\n  A red fruit.\n");
+}
+
+#[test]
+fn test_definition_lists_block_descriptions_are_indented() {
+    let html = "<dl><div><dt><p>Code</p></dt>\
+        <dd><p>First.</p><pre><code>let x = 1;</code></pre></dd></div>\
+        <dt>Short</dt><dd>One line.</dd></dl>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_definition_lists(true)),
+        "Code\n: First.\n\n    ```\n    let x = 1;\n    ```\n\nShort\n: One line.\n"
+    );
+}
+
+#[test]
+fn test_definition_lists_term_is_one_line() {
+    let html = "<dl><dt>Two<br>lines</dt><dd>Yes.</dd></dl>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_definition_lists(true)),
+        "Two lines\n: Yes.\n"
+    );
+}
+
+#[test]
+fn test_definition_lists_description_without_term_falls_back() {
+    let html = "<dl><dd>Orphan.</dd><dt>Term</dt><dd>Defined.</dd></dl>";
+    assert_eq!(
+        convert_with(html, &Options::new().with_definition_lists(true)),
+        convert(html),
+    );
+}
+
+const SPHINX: &str = r#"<div class="admonition warning"><p class="admonition-title">Warning</p><p>Back up <em>first</em>.</p></div>"#;
+
+#[test]
+fn test_admonitions_github_alert() {
+    assert_eq!(
+        convert_with(
+            SPHINX,
+            &Options::new().with_admonition_style(AdmonitionStyle::Alert)
+        ),
+        "> [!WARNING]\n> Back up *first*.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_directive() {
+    assert_eq!(
+        convert_with(
+            SPHINX,
+            &Options::new().with_admonition_style(AdmonitionStyle::Directive)
+        ),
+        ":::warning\nBack up *first*.\n:::\n"
+    );
+}
+
+#[test]
+fn test_admonitions_obsidian_callout() {
+    let html = r#"<aside class="note"><p class="title">Heads up</p><p>One.</p><p>Two.</p></aside>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Callout)
+        ),
+        "> [!note] Heads up\n> One.\n>\n> Two.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_alert_title_leads_in_bold() {
+    let html = r#"<div role="note"><p class="admonition-title">Heads up</p>Text.</div>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Alert)
+        ),
+        "> [!NOTE]\n> **Heads up**\n>\n> Text.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_directive_title_and_nesting() {
+    let html = r#"<div class="admonition tip"><p class="admonition-title">Nice [trick]</p>
+        <div class="admonition danger"><p>Inner.</p></div></div>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Directive)
+        ),
+        "::::tip[Nice \\[trick\\]]\n:::caution\nInner.\n:::\n::::\n"
+    );
+}
+
+#[test]
+fn test_admonitions_docusaurus_and_confluence() {
+    let html = r#"<div class="theme-admonition alert alert--danger admonition_x"><div class="admonitionHeading_y"><span class="admonitionIcon_z"></span>danger</div><div class="admonitionContent_w"><p>Hot.</p></div></div>
+<div class="confluence-information-macro confluence-information-macro-tip"><span class="aui-icon"></span><div class="confluence-information-macro-body"><p>Save often.</p></div></div>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Alert)
+        ),
+        "> [!CAUTION]\n> Hot.\n\n> [!TIP]\n> Save often.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_custom_kinds() {
+    let html = r#"<div class="panel panel-danger"><p>Stop.</p></div><div class="plain"><p>Text.</p></div>"#;
+    let options = Options::new()
+        .with_admonition_style(AdmonitionStyle::Directive)
+        .with_admonition_kind("panel-danger", "Danger");
+    assert_eq!(
+        convert_with(html, &options),
+        ":::danger\nStop.\n:::\n\nText.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_bare_kind_classes_need_a_marker() {
+    let html = r#"<div class="info"><p>Sidebar.</p></div><section class="error note"><p>Page.</p></section>
+<div class="note callout-box"><p>Marked.</p></div>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Alert)
+        ),
+        "Sidebar.\n\nPage.\n\n> [!NOTE]\n> Marked.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_callout_title_is_escaped() {
+    let html = r#"<aside class="tip"><p class="title">Use *this* [x]</p><p>One.</p></aside>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Callout)
+        ),
+        "> [!tip] Use \\*this\\* \\[x]\n> One.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_nested_alert_is_a_blockquote() {
+    let html = r#"<div class="admonition tip"><p>Outer.</p>
+        <div class="admonition danger"><p class="admonition-title">Careful</p><p>Inner.</p></div></div>"#;
+    assert_eq!(
+        convert_with(
+            html,
+            &Options::new().with_admonition_style(AdmonitionStyle::Alert)
+        ),
+        "> [!TIP]\n> Outer.\n>\n> > **Careful**\n> >\n> > Inner.\n"
+    );
+}
+
+#[test]
+fn test_admonitions_off_by_default() {
+    assert_eq!(convert(SPHINX), "Warning\n\nBack up *first*.\n");
+}
@@ -13,8 +13,8 @@ fn image_alt_with_bracket() {
 
 /// Definition labels and link-reference labels are escaped via `escape_link_text`
 /// to prevent `]` from prematurely closing the bracket.
-/// Unit-tested in src/stringify/escape.rs (can't test via convert() because our
-/// HTML→MDAST transformer never produces Definition/LinkReference nodes).
+/// Unit-tested in src/stringify/escape.rs; the transformer only produces
+/// Definition/LinkReference nodes with `LinkStyle::Reference`.
 /// The fix is: `handle_definition` and `handle_link_reference` call
 /// `escape_link_text(raw_label)` before formatting the output.
 #[test]
@@ -25,6 +25,10 @@ fn definition_label_escaping_documented() {
         md.contains("foo\\]bar"),
         "link text ] should be escaped: {md:?}"
     );
+    let options =
+        html2markdown::Options::new().with_link_style(html2markdown::LinkStyle::Reference);
+    let md = html2markdown::convert_with(r#"<a href="http://example.com">foo]bar</a>"#, &options);
+    assert_eq!(md, "[foo\\]bar][1]\n\n[1]: http://example.com\n");
 }
 
 /// Image alt text containing `*` must be escaped to prevent accidental
@@ -154,6 +158,7 @@ fn atx_heading_leading_space() {
             children: vec![Node::Text(Text {
                 value: " foo".to_string(),
             })],
+            attributes: Attributes::default(),
         })],
     });
     let md = html2markdown::mdast_to_string(&node, &StringifyOptions::default());
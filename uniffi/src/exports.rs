@@ -200,6 +200,7 @@ fn to_core_stringify_options(
         quote: parse_char(&opts.quote, "quote", &['"', '\''])?,
         fences: opts.fences,
         resource_link: opts.resource_link,
+        ..Default::default()
     })
 }
 
@@ -210,5 +211,6 @@ fn to_core_options(opts: Options) -> Result<html2markdown::Options, OptionsError
         checked: opts.checked,
         unchecked: opts.unchecked,
         quotes: opts.quotes,
+        ..Default::default()
     })
 }